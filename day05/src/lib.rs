@@ -1,78 +1,197 @@
-use std::{iter::once, str::FromStr};
+use std::{collections::HashMap, ops::Range, str::FromStr};
 
-use anyhow::{bail, Context, Ok, Result};
+use anyhow::{bail, Context, Result};
 
 use itertools::Itertools;
-use tap::Tap;
 
 pub fn day5_part1(input: &str) -> Result<isize> {
-    let mut iter = input.split("\n\n");
-    let seeds = iter
-        .next()
-        .and_then(|s| s.split_once(':'))
-        .context("Parse Seeds Fail")?
-        .1
-        .split_ascii_whitespace()
-        .map(|s| s.parse())
-        .collect::<Result<Vec<_>, _>>()?;
-
-    let tables = iter
-        .map(|table| table.parse::<Table>())
-        .process_results(|iter| iter.collect::<Vec<Table>>())?;
-
-    tables
-        .iter()
-        .fold(seeds, |seeds, table| table.map_to_targets(seeds))
-        .iter()
+    let almanac = input.parse::<Almanac>()?;
+
+    almanac
+        .convert_all("seed", "location", &almanac.seeds)?
+        .into_iter()
         .min()
         .context("No Result")
-        .copied()
 }
 
 pub fn day5_part2(input: &str) -> Result<isize> {
-    let mut iter = input.split("\n\n");
-
-    let seeds = iter
-        .next()
-        .and_then(|s| s.split_once(':'))
-        .context("Parse Seeds Fail")?
-        .1
-        .split_ascii_whitespace()
-        .map(|s| s.parse())
-        .process_results(|iter| {
-            iter.chunks(2)
-                .into_iter()
-                .flat_map(|mut c| {
-                    let start = c.next().unwrap();
-                    let range = c.next().unwrap();
-                    start..start + range
-                })
-                .collect::<Vec<_>>()
-        })?
-        .tap(|v| println!("{:#?}", v));
-
-    let tables = iter
-        .map(|table| table.parse::<Table>())
-        .process_results(|iter| iter.collect::<Vec<Table>>())?;
-
-    tables
-        .iter()
-        .fold(seeds, |seeds, table| table.map_to_targets(seeds))
-        .iter()
-        .min()
-        .context("No Result")
-        .copied()
+    let almanac = input.parse::<Almanac>()?;
+
+    almanac
+        .lowest_location(&almanac.seed_ranges())
+        .map(|(location, _)| location)
 }
 
 #[derive(Debug, PartialEq)]
-struct Mapping {
-    source: isize,
-    target: isize,
-    range: isize,
+pub struct Almanac {
+    seeds: Vec<isize>,
+    tables: Vec<Table>,
 }
 
-#[derive(Debug, PartialEq)]
-struct Table(Vec<Mapping>);
+impl FromStr for Almanac {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let mut iter = s.split("\n\n");
+        let seeds = iter
+            .next()
+            .and_then(|s| s.split_once(':'))
+            .context("Parse Seeds Fail")?
+            .1
+            .split_ascii_whitespace()
+            .map(|s| s.parse())
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let tables = iter
+            .map(|table| table.parse::<Table>())
+            .process_results(|iter| iter.collect::<Vec<Table>>())?;
+
+        Almanac::new(seeds, tables)
+    }
+}
+
+impl Almanac {
+    /// Every category may have at most one outgoing table and following the
+    /// tables must never lead back to a category already visited.
+    pub fn new(seeds: Vec<isize>, tables: Vec<Table>) -> Result<Self> {
+        let mut outgoing = HashMap::new();
+        for table in &tables {
+            if outgoing.insert(table.source.as_str(), table).is_some() {
+                bail!("Several Tables From Category {}", table.source);
+            }
+        }
+
+        for table in &tables {
+            let mut category = table.target.as_str();
+            for _ in 0..tables.len() {
+                if category == table.source {
+                    bail!("Cycle Through Category {}", category);
+                }
+                match outgoing.get(category) {
+                    Some(next) => category = &next.target,
+                    None => break,
+                }
+            }
+        }
+
+        Ok(Almanac { seeds, tables })
+    }
+
+    pub fn seeds(&self) -> &[isize] {
+        &self.seeds
+    }
+
+    pub fn tables(&self) -> &[Table] {
+        &self.tables
+    }
+
+    /// Tables leading from category `from` to category `to`, in order. A
+    /// path never uses more tables than there are, so a longer one loops.
+    pub fn path(&self, from: &str, to: &str) -> Result<Vec<&Table>> {
+        let mut path = vec![];
+        let mut category = from;
+
+        for _ in 0..=self.tables.len() {
+            if category == to {
+                return Ok(path);
+            }
+            let table = self
+                .tables
+                .iter()
+                .find(|table| table.source == category)
+                .with_context(|| format!("No Path From {} To {}", from, to))?;
+            path.push(table);
+            category = &table.target;
+        }
+
+        bail!("Cycle Through Category {}", category)
+    }
+
+    /// Single table mapping category `from` straight to category `to`.
+    pub fn table(&self, from: &str, to: &str) -> Result<Table> {
+        Ok(self
+            .path(from, to)?
+            .into_iter()
+            .fold(Table::identity(from, from), |acc, table| acc.compose(table)))
+    }
+
+    pub fn convert(&self, from: &str, to: &str, value: isize) -> Result<isize> {
+        Ok(self.table(from, to)?.lookup(value))
+    }
+
+    pub fn convert_all(&self, from: &str, to: &str, values: &[isize]) -> Result<Vec<isize>> {
+        let table = self.table(from, to)?;
+
+        Ok(values.iter().map(|value| table.lookup(*value)).collect())
+    }
+
+    /// Collapse the whole seed-to-location chain into a single table.
+    pub fn collapse(&self) -> Result<Table> {
+        self.table("seed", "location")
+    }
+
+    /// Seeds read as `start range` pairs.
+    pub fn seed_ranges(&self) -> Vec<Range<isize>> {
+        self.seeds
+            .iter()
+            .tuples()
+            .map(|(start, range)| *start..start + range)
+            .collect()
+    }
+
+    /// Smallest location reachable from `seeds`, together with the seed
+    /// interval whose first seed lands on it.
+    pub fn lowest_location(&self, seeds: &[Range<isize>]) -> Result<(isize, Range<isize>)> {
+        let table = self.collapse()?;
+
+        seeds
+            .iter()
+            .flat_map(|range| table.segments(range.clone()))
+            .map(|(range, target)| (target, range))
+            .min_by_key(|(location, _)| *location)
+            .context("No Seeds")
+    }
+
+    /// Seed intervals that end up somewhere in `locations`.
+    pub fn seeds_for_locations(&self, locations: Range<isize>) -> Result<Vec<Range<isize>>> {
+        Ok(self
+            .path("seed", "location")?
+            .into_iter()
+            .rev()
+            .fold(vec![locations], |ranges, table| {
+                normalize(ranges.into_iter().flat_map(|range| table.preimage(range)))
+            }))
+    }
+}
+
+/// Sort ranges and merge the ones that overlap or touch.
+fn normalize(ranges: impl Iterator<Item = Range<isize>>) -> Vec<Range<isize>> {
+    ranges
+        .filter(|range| !range.is_empty())
+        .sorted_by_key(|range| range.start)
+        .coalesce(|a, b| {
+            if b.start <= a.end {
+                Ok(a.start..a.end.max(b.end))
+            } else {
+                Err((a, b))
+            }
+        })
+        .collect()
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Mapping {
+    pub source: isize,
+    pub target: isize,
+    pub range: isize,
+}
+
+#[derive(Debug, PartialEq, Clone)]
+pub struct Table {
+    pub source: String,
+    pub target: String,
+    mappings: Vec<Mapping>,
+}
 
 impl FromStr for Mapping {
     type Err = anyhow::Error;
@@ -99,49 +218,223 @@ impl FromStr for Table {
     type Err = anyhow::Error;
 
     fn from_str(s: &str) -> Result<Self> {
-        let table = s
-            .lines()
-            .skip(1)
+        let mut lines = s.lines();
+        let (source, target) = lines
+            .next()
+            .and_then(|header| header.strip_suffix(" map:"))
+            .and_then(|header| header.split_once("-to-"))
+            .context("Parse Header Fail")?;
+
+        lines
             .map(|line| line.parse::<Mapping>())
-            .process_results(|iter| {
-                once(Mapping {
-                    source: 0,
-                    target: 0,
-                    range: 0,
-                })
-                .chain(iter.sorted_by_key(|mapping| mapping.source))
-                .rev()
-                .collect()
-            })?;
-
-        Ok(Table(table))
+            .process_results(|iter| Table::new(source, target, iter))?
     }
 }
 
 impl Table {
-    fn map_to_targets(&self, input: Vec<isize>) -> Vec<isize> {
-        let mut input_iter = input.iter().sorted().rev();
-
-        if let Some(mut i) = input_iter.next() {
-            self.0.iter().fold(vec![], |mut acc, mapping| {
-                while i >= &mapping.source {
-                    if *i > mapping.source + mapping.range {
-                        acc.push(*i);
-                    } else {
-                        acc.push(mapping.target + i - mapping.source);
-                    }
-                    i = input_iter.next().unwrap_or(&isize::MIN);
+    /// Mappings are half-open `[source, source + range)` intervals, kept
+    /// sorted by source. Overlapping mappings are rejected.
+    pub fn new(
+        source: &str,
+        target: &str,
+        mappings: impl IntoIterator<Item = Mapping>,
+    ) -> Result<Self> {
+        let mappings = mappings
+            .into_iter()
+            .filter(|mapping| mapping.range != 0)
+            .sorted_by_key(|mapping| mapping.source)
+            .collect_vec();
+
+        for mapping in &mappings {
+            if mapping.range < 0 {
+                bail!("Negative Range: {:?}", mapping);
+            }
+            if mapping.source.checked_add(mapping.range).is_none()
+                || mapping.target.checked_add(mapping.range).is_none()
+            {
+                bail!("Range Overflow: {:?}", mapping);
+            }
+        }
+
+        if let Some((a, b)) = mappings
+            .iter()
+            .tuple_windows()
+            .find(|(a, b)| a.source + a.range > b.source)
+        {
+            bail!("Overlapping Mappings: {:?} and {:?}", a, b);
+        }
+
+        Ok(Table {
+            source: source.to_string(),
+            target: target.to_string(),
+            mappings,
+        })
+    }
+
+    fn identity(source: &str, target: &str) -> Self {
+        Table {
+            source: source.to_string(),
+            target: target.to_string(),
+            mappings: vec![],
+        }
+    }
+
+    /// Mappings in ascending source order.
+    pub fn mappings(&self) -> impl Iterator<Item = &Mapping> {
+        self.mappings.iter()
+    }
+
+    pub fn lookup(&self, value: isize) -> isize {
+        let index = self
+            .mappings
+            .partition_point(|mapping| mapping.source <= value);
+
+        match index.checked_sub(1).map(|index| &self.mappings[index]) {
+            Some(mapping) if value < mapping.source + mapping.range => {
+                mapping.target + (value - mapping.source)
+            }
+            _ => value,
+        }
+    }
+
+    /// Split `range` into pieces that each map onto a contiguous run of
+    /// targets, paired with the target of their first value.
+    fn segments(&self, range: Range<isize>) -> Vec<(Range<isize>, isize)> {
+        let mut cursor = range.start;
+        let mut segments = vec![];
+
+        for mapping in self.mappings() {
+            let start = mapping.source.max(cursor);
+            let end = (mapping.source + mapping.range).min(range.end);
+            if start >= end {
+                continue;
+            }
+            if cursor < start {
+                segments.push((cursor..start, cursor));
+            }
+            segments.push((start..end, mapping.target + (start - mapping.source)));
+            cursor = end;
+        }
+
+        if cursor < range.end {
+            segments.push((cursor..range.end, cursor));
+        }
+
+        segments
+    }
+
+    /// Build the table equivalent to applying `self` and then `other`.
+    pub fn compose(&self, other: &Table) -> Table {
+        let Some((start, end)) = self
+            .mappings()
+            .chain(other.mappings())
+            .flat_map(|mapping| [mapping.source, mapping.source + mapping.range])
+            .minmax()
+            .into_option()
+        else {
+            return Table::identity(&self.source, &other.target);
+        };
+
+        let mappings = self
+            .segments(start..end)
+            .into_iter()
+            .flat_map(|(range, target)| {
+                other
+                    .segments(target..target + (range.end - range.start))
+                    .into_iter()
+                    .map(move |(r, t)| {
+                        let start = range.start + (r.start - target);
+                        (start..start + (r.end - r.start), t)
+                    })
+            })
+            .filter(|(range, target)| range.start != *target)
+            .coalesce(|(a, a_target), (b, b_target)| {
+                if a.end == b.start && a_target + (a.end - a.start) == b_target {
+                    Ok((a.start..b.end, a_target))
+                } else {
+                    Err(((a, a_target), (b, b_target)))
                 }
-                acc
             })
-        } else {
-            vec![]
+            .map(|(range, target)| Mapping {
+                source: range.start,
+                target,
+                range: range.end - range.start,
+            })
+            .collect_vec();
+
+        Table {
+            source: self.source.clone(),
+            target: other.target.clone(),
+            mappings,
+        }
+    }
+
+    /// Swap every mapping to target-to-source form. Only possible when the
+    /// targets cover exactly the same values as the sources, otherwise some
+    /// values would have no or several preimages.
+    pub fn invert(&self) -> Result<Table> {
+        let sources = self
+            .mappings()
+            .map(|mapping| mapping.source..mapping.source + mapping.range)
+            .collect_vec();
+        let targets = self
+            .mappings()
+            .map(|mapping| mapping.target..mapping.target + mapping.range)
+            .sorted_by_key(|range| range.start)
+            .collect_vec();
+
+        if targets.iter().tuple_windows().any(|(a, b)| a.end > b.start) {
+            bail!("Overlapping Targets");
+        }
+        if normalize(sources.into_iter()) != normalize(targets.into_iter()) {
+            bail!("Targets Do Not Cover Sources");
         }
+
+        Table::new(
+            &self.target,
+            &self.source,
+            self.mappings().map(|mapping| Mapping {
+                source: mapping.target,
+                target: mapping.source,
+                range: mapping.range,
+            }),
+        )
+    }
+
+    /// Values that map into `range`.
+    pub fn preimage(&self, range: Range<isize>) -> Vec<Range<isize>> {
+        let mapped = self.mappings().filter_map(|mapping| {
+            let start = mapping.target.max(range.start);
+            let end = (mapping.target + mapping.range).min(range.end);
+
+            (start < end).then(|| {
+                mapping.source + (start - mapping.target)..mapping.source + (end - mapping.target)
+            })
+        });
+        let unmapped = self
+            .segments(range.clone())
+            .into_iter()
+            .filter(|(range, target)| range.start == *target)
+            .map(|(range, _)| range);
+
+        normalize(mapped.chain(unmapped))
+    }
+
+    /// Targets of `input`, in descending order of the source values.
+    pub fn map_to_targets(&self, input: Vec<isize>) -> Vec<isize> {
+        input
+            .into_iter()
+            .sorted()
+            .rev()
+            .map(|value| self.lookup(value))
+            .collect()
     }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -187,26 +480,30 @@ mod tests {
 52 50 48";
 
         let table = input.parse::<Table>().unwrap();
+        assert_eq!(table.source, "seed");
+        assert_eq!(table.target, "soil");
         assert_eq!(
-            table,
-            Table(vec![
-                Mapping {
-                    source: 98,
-                    target: 50,
-                    range: 2
-                },
+            table.mappings,
+            vec![
                 Mapping {
                     source: 50,
                     target: 52,
                     range: 48
                 },
                 Mapping {
-                    source: 0,
-                    target: 0,
-                    range: 0
+                    source: 98,
+                    target: 50,
+                    range: 2
                 },
-            ])
+            ]
         );
+
+        assert!("seed-to-soil\n50 98 2".parse::<Table>().is_err());
+        assert!("seed map:\n50 98 2".parse::<Table>().is_err());
+        assert!("seed-to-soil map:\n50 98 3\n0 100 2"
+            .parse::<Table>()
+            .is_err());
+        assert!("seed-to-soil map:\n50 98 -2".parse::<Table>().is_err());
     }
 
     #[test]
@@ -221,4 +518,260 @@ mod tests {
 
         assert_eq!(table.map_to_targets(input), vec![81, 57, 14, 13]);
     }
+
+    #[test]
+    fn test_lookup_boundaries() {
+        let table = "seed-to-soil map:
+50 98 2
+52 50 48"
+            .parse::<Table>()
+            .unwrap();
+
+        assert_eq!(table.lookup(49), 49);
+        assert_eq!(table.lookup(50), 52);
+        assert_eq!(table.lookup(97), 99);
+        assert_eq!(table.lookup(98), 50);
+        assert_eq!(table.lookup(99), 51);
+        assert_eq!(table.lookup(100), 100);
+
+        // Near the top of the range, adding the target before subtracting
+        // the source would overflow.
+        let max = isize::MAX;
+        let table = format!("seed-to-soil map:\n{} {} 5", max - 10, max - 20)
+            .parse::<Table>()
+            .unwrap();
+        assert_eq!(table.lookup(max - 18), max - 8);
+        assert_eq!(table.compose(&table).lookup(max - 18), max - 8);
+        assert_eq!(
+            table.preimage(max - 8..max - 7),
+            vec![max - 18..max - 17, max - 8..max - 7]
+        );
+    }
+
+    fn naive_lookup(mappings: &[Mapping], value: isize) -> isize {
+        mappings
+            .iter()
+            .find(|mapping| mapping.source <= value && value < mapping.source + mapping.range)
+            .map_or(value, |mapping| mapping.target + (value - mapping.source))
+    }
+
+    fn overlapping(mappings: &[Mapping]) -> bool {
+        mappings.iter().tuple_combinations().any(|(a, b)| {
+            a.range > 0
+                && b.range > 0
+                && a.source < b.source + b.range
+                && b.source < a.source + a.range
+        })
+    }
+
+    prop_compose! {
+        fn mappings()(
+            pieces in prop::collection::vec((0..20isize, 1..20isize, 0..200isize), 0..8)
+        ) -> Vec<Mapping> {
+            pieces
+                .into_iter()
+                .scan(0, |cursor, (gap, range, target)| {
+                    let source = *cursor + gap;
+                    *cursor = source + range;
+                    Some(Mapping { source, target, range })
+                })
+                .collect_vec()
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_lookup_matches_naive(
+            mappings in mappings().prop_shuffle(),
+            values in prop::collection::vec(-10..300isize, 20)
+        ) {
+            let table = Table::new("a", "b", mappings.clone()).unwrap();
+
+            for value in values {
+                prop_assert_eq!(table.lookup(value), naive_lookup(&mappings, value));
+            }
+        }
+
+        #[test]
+        fn prop_overlap_detected(
+            mappings in prop::collection::vec((0..50isize, 0..50isize, 0..10isize), 0..5)
+        ) {
+            let mappings = mappings
+                .into_iter()
+                .map(|(source, target, range)| Mapping { source, target, range })
+                .collect_vec();
+
+            prop_assert_eq!(
+                Table::new("a", "b", mappings.clone()).is_err(),
+                overlapping(&mappings)
+            );
+        }
+
+        #[test]
+        fn prop_compose_matches_naive(first in mappings(), second in mappings()) {
+            let composed = Table::new("a", "b", first.clone())
+                .unwrap()
+                .compose(&Table::new("b", "c", second.clone()).unwrap());
+
+            for value in -10..300 {
+                prop_assert_eq!(
+                    composed.lookup(value),
+                    naive_lookup(&second, naive_lookup(&first, value))
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn test_compose() {
+        let first = "seed-to-soil map:
+50 98 2
+52 50 48"
+            .parse::<Table>()
+            .unwrap();
+        let second = "soil-to-fertilizer map:
+0 15 37
+37 52 2
+39 0 15"
+            .parse::<Table>()
+            .unwrap();
+
+        let composed = first.compose(&second);
+
+        for seed in 0..120 {
+            assert_eq!(
+                composed.lookup(seed),
+                second.lookup(first.lookup(seed)),
+                "seed {}",
+                seed
+            );
+        }
+    }
+
+    #[test]
+    fn test_collapse() {
+        let almanac = include_str!("../example").parse::<Almanac>().unwrap();
+        let table = almanac.collapse().unwrap();
+
+        for seed in 0..120 {
+            let expected = almanac
+                .tables
+                .iter()
+                .fold(seed, |value, table| table.lookup(value));
+            assert_eq!(table.lookup(seed), expected, "seed {}", seed);
+        }
+
+        assert_eq!(table.lookup(79), 82);
+        assert_eq!(table.lookup(14), 43);
+        assert_eq!(table.lookup(55), 86);
+        assert_eq!(table.lookup(13), 35);
+    }
+
+    #[test]
+    fn test_invert() {
+        let table = "seed-to-soil map:
+50 98 2
+52 50 48"
+            .parse::<Table>()
+            .unwrap();
+        let inverse = table.invert().unwrap();
+
+        for seed in 0..120 {
+            assert_eq!(inverse.lookup(table.lookup(seed)), seed);
+        }
+
+        let table = "seed-to-soil map:
+0 10 5"
+            .parse::<Table>()
+            .unwrap();
+        assert!(table.invert().is_err());
+    }
+
+    #[test]
+    fn test_preimage() {
+        let table = "seed-to-soil map:
+0 10 5"
+            .parse::<Table>()
+            .unwrap();
+
+        assert_eq!(table.preimage(3..12), vec![3..10, 13..15]);
+        assert_eq!(table.preimage(12..20), vec![15..20]);
+    }
+
+    #[test]
+    fn test_reverse_query() {
+        let almanac = include_str!("../example").parse::<Almanac>().unwrap();
+
+        let seeds = almanac.seeds_for_locations(35..36).unwrap();
+        assert_eq!(seeds, vec![13..14]);
+
+        let seeds = almanac.seeds_for_locations(0..60).unwrap();
+        let table = almanac.collapse().unwrap();
+        for seed in 0..120 {
+            let inside = seeds.iter().any(|range| range.contains(&seed));
+            assert_eq!(inside, table.lookup(seed) < 60, "seed {}", seed);
+        }
+
+        let (location, range) = almanac.lowest_location(&almanac.seed_ranges()).unwrap();
+        assert_eq!(location, 46);
+        assert_eq!(range.start, 82);
+    }
+
+    #[test]
+    fn test_convert_by_category() {
+        let almanac = include_str!("../example").parse::<Almanac>().unwrap();
+
+        assert_eq!(almanac.convert("seed", "humidity", 79).unwrap(), 78);
+        assert_eq!(almanac.convert("seed", "seed", 79).unwrap(), 79);
+        assert_eq!(
+            almanac
+                .convert_all("fertilizer", "location", &[81, 53, 57])
+                .unwrap(),
+            vec![82, 43, 86]
+        );
+        assert!(almanac.convert("location", "seed", 82).is_err());
+        assert!(almanac.convert("seed", "unknown", 79).is_err());
+    }
+
+    #[test]
+    fn test_reject_invalid_almanac() {
+        let cyclic = "seeds: 1
+
+seed-to-soil map:
+0 1 1
+
+soil-to-seed map:
+1 0 1";
+        assert!(cyclic.parse::<Almanac>().is_err());
+
+        let ambiguous = "seeds: 1
+
+seed-to-soil map:
+0 1 1
+
+seed-to-water map:
+1 0 1";
+        assert!(ambiguous.parse::<Almanac>().is_err());
+
+        let self_loop = "seeds: 1
+
+seed-to-seed map:
+0 1 1";
+        assert!(self_loop.parse::<Almanac>().is_err());
+    }
+
+    #[test]
+    fn test_path_stops_on_cycle() {
+        let tables = ["seed-to-soil map:\n0 1 1", "soil-to-seed map:\n1 0 1"]
+            .iter()
+            .map(|table| table.parse::<Table>().unwrap())
+            .collect();
+        let almanac = Almanac {
+            seeds: vec![1],
+            tables,
+        };
+
+        assert!(almanac.convert("seed", "location", 1).is_err());
+        assert_eq!(almanac.path("seed", "soil").unwrap().len(), 1);
+    }
 }
@@ -1,5 +1,9 @@
+use std::{iter::once, ops::RangeInclusive};
+
 use anyhow::{anyhow, Context, Result};
 use itertools::Itertools;
+use num_integer::{Integer, Roots};
+use num_traits::{CheckedAdd, CheckedMul, CheckedSub, NumRef, One, RefNum, Zero};
 #[allow(unused_imports)]
 use tap::Tap;
 
@@ -21,39 +25,12 @@ fn day6_part1_v1(input: &str) -> Result<usize> {
     Ok(result)
 }
 
-pub fn day6_part1_v2(input: &str) -> Result<usize> {
+pub fn day6_part1_v2(input: &str) -> Result<Number> {
     let rounds = parse_round(input)?;
 
-    let result = rounds
-        .iter()
-        .map(|round| {
-            // let function = | x | x * (round.time - x);
-            // a = 1; b = -round.time; c = round.distance;
-
-            round
-                .time
-                .pow(2)
-                .checked_add_signed(-4 * round.distance as isize)
-                .context("Overflow")
-                .map(|discriminant| {
-                    let b = round.time as f64;
-                    let factor = (discriminant as f64).sqrt();
-                    let start = (b - factor) / 2.0;
-                    let end = (b + factor) / 2.0;
-
-                    let start = (if start.ceil() == start {
-                        start + 1.0
-                    } else {
-                        start.ceil()
-                    }) as usize;
-                    let end = end.ceil() as usize;
-
-                    (start..end).len()
-                })
-        })
-        .process_results(|iter| iter.product::<usize>())?;
-
-    Ok(result)
+    rounds.iter().try_fold(Number::one(), |acc, round| {
+        CheckedMul::checked_mul(&acc, &round.ways_to_win()?).context("Overflow")
+    })
 }
 
 #[allow(dead_code)]
@@ -74,61 +51,338 @@ fn day6_part2_v1(input: &str) -> Result<usize> {
     Ok(result)
 }
 
-pub fn day6_part2_v2(input: &str) -> Result<usize> {
+pub fn day6_part2_v2(input: &str) -> Result<Number> {
     let rounds = parse_round(&input.replace(' ', ""))?;
 
-    let result = rounds
-        .iter()
-        .map(|round| {
-            // let function = | x | x * (round.time - x);
-            // a = 1; b = -round.time; c = round.distance;
+    rounds.iter().try_fold(Number::zero(), |acc, round| {
+        CheckedAdd::checked_add(&acc, &round.ways_to_win()?).context("Overflow")
+    })
+}
 
-            round
-                .time
-                .pow(2)
-                .checked_add_signed(-4 * round.distance as isize)
-                .context("Overflow")
-                .map(|discriminant| {
-                    let b = round.time as f64;
-                    let factor = (discriminant as f64).sqrt();
-                    let start = (b - factor) / 2.0;
-                    let end = (b + factor) / 2.0;
-
-                    let start = (if start.ceil() == start {
-                        start + 1.0
-                    } else {
-                        start.ceil()
-                    }) as usize;
-                    let end = end.ceil() as usize;
-
-                    (start..end).len()
-                })
-        })
-        .process_results(|iter| iter.sum())?;
+/// Race values are kept in `u128` and fail to parse once they no longer
+/// fit. The `bigint` feature swaps in an arbitrary precision backend.
+#[cfg(not(feature = "bigint"))]
+pub type Number = u128;
+#[cfg(feature = "bigint")]
+pub type Number = num_bigint::BigUint;
 
-    Ok(result)
+#[derive(Debug, PartialEq)]
+pub struct Round {
+    pub time: Number,
+    pub distance: Number,
+}
+
+impl Round {
+    fn get_win_times(&self) -> Vec<Number> {
+        brute_force(&self.time, &self.distance)
+    }
+
+    fn win_interval(&self) -> Result<Option<RangeInclusive<Number>>> {
+        solve(&self.time, &self.distance)
+    }
+
+    fn ways_to_win(&self) -> Result<Number> {
+        Ok(self.win_interval()?.map_or(Number::zero(), |interval| {
+            interval.end() - interval.start() + Number::one()
+        }))
+    }
+
+    pub fn win_interval_with<M: RaceModel<Number>>(
+        &self,
+        model: &M,
+    ) -> Result<Option<RangeInclusive<Number>>> {
+        solve_model(model, &self.time, &self.distance)
+    }
+
+    pub fn strategy(&self) -> Result<HoldStrategy<Number>> {
+        self.strategy_with(&LinearCharge)
+    }
+
+    pub fn strategy_with<M: RaceModel<Number>>(&self, model: &M) -> Result<HoldStrategy<Number>> {
+        plan(model, &self.time, &self.distance)
+    }
+
+    pub fn ways_to_win_with<M: RaceModel<Number>>(&self, model: &M) -> Result<Number> {
+        Ok(self
+            .win_interval_with(model)?
+            .map_or(Number::zero(), |interval| {
+                interval.end() - interval.start() + Number::one()
+            }))
+    }
 }
 
 #[derive(Debug, PartialEq)]
-struct Round {
-    time: usize,
-    distance: usize,
+pub struct HoldStrategy<N> {
+    pub optimal_hold: N,
+    pub max_distance: N,
+    /// How far the best run beats the record, if it beats it at all.
+    pub margin: Option<N>,
+    pub ways_to_win: N,
+    /// How many milliseconds the hold may be off the optimum in either
+    /// direction and still win.
+    pub sensitivity: Option<N>,
 }
 
-impl Round {
-    fn get_win_times(&self) -> Vec<usize> {
-        (0..self.time)
-            .map(|t| {
-                let speed = t;
-                let time = self.time - t;
-                (t, speed * time)
-            })
-            .filter_map(|(t, d)| if d > self.distance { Some(t) } else { None })
-            .collect::<_>()
+fn plan<N, M>(model: &M, time: &N, record: &N) -> Result<HoldStrategy<N>>
+where
+    N: NumRef + Ord + Clone + CheckedAdd,
+    M: RaceModel<N>,
+    for<'a> &'a N: RefNum<N>,
+{
+    let optimal_hold = peak_hold(model, time)?;
+    let max_distance = model.distance(time, &optimal_hold).context("Overflow")?;
+    let win_interval = solve_model(model, time, record)?;
+
+    let margin = (max_distance > *record).then(|| &max_distance - record);
+    let sensitivity = win_interval
+        .as_ref()
+        .map(|interval| (&optimal_hold - interval.start()).min(interval.end() - &optimal_hold));
+    let ways_to_win = match win_interval {
+        Some(interval) => (interval.end() - interval.start())
+            .checked_add(&N::one())
+            .context("Overflow")?,
+        None => N::zero(),
+    };
+
+    Ok(HoldStrategy {
+        optimal_hold,
+        max_distance,
+        margin,
+        ways_to_win,
+        sensitivity,
+    })
+}
+
+pub fn strategy_report(rounds: &[Round]) -> Result<String> {
+    let header = format!(
+        "{:>4} {:>16} {:>20} {:>10} {:>20} {:>20} {:>10} {:>11}",
+        "Race", "Time", "Record", "Best Hold", "Max Distance", "Margin", "Ways", "Sensitivity"
+    );
+    let or_dash = |value: Option<Number>| value.map_or("-".to_string(), |v| v.to_string());
+
+    let rows = rounds
+        .iter()
+        .enumerate()
+        .map(|(index, round)| {
+            let strategy = round.strategy()?;
+            Ok(format!(
+                "{:>4} {:>16} {:>20} {:>10} {:>20} {:>20} {:>10} {:>11}",
+                index + 1,
+                round.time,
+                round.distance,
+                strategy.optimal_hold,
+                strategy.max_distance,
+                or_dash(strategy.margin),
+                strategy.ways_to_win,
+                or_dash(strategy.sensitivity),
+            ))
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok(once(header).chain(rows).join("\n"))
+}
+
+/// How far the boat travels when the button is held for `hold` out of
+/// `time` milliseconds. The distance has to rise strictly up to its peak
+/// and must not rise again afterwards, so the winning holds form a single
+/// interval that bisection can find. `None` means the distance does not fit
+/// in `N`.
+pub trait RaceModel<N> {
+    fn distance(&self, time: &N, hold: &N) -> Option<N>;
+}
+
+/// Each millisecond of holding adds one unit of speed.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct LinearCharge;
+
+/// Each millisecond of holding adds `rate` units of speed.
+#[derive(Debug, Clone)]
+pub struct ChargeRate<N> {
+    pub rate: N,
+}
+
+/// Speed charges at `rate` per millisecond but never exceeds `top_speed`.
+#[derive(Debug, Clone)]
+pub struct CappedSpeed<N> {
+    pub rate: N,
+    pub top_speed: N,
+}
+
+/// The first millisecond adds `rate` units of speed and every following one
+/// adds `drag` units less, until holding gains nothing more.
+#[derive(Debug, Clone)]
+pub struct DragLimited<N> {
+    pub rate: N,
+    pub drag: N,
+}
+
+impl<N> RaceModel<N> for LinearCharge
+where
+    N: CheckedMul + CheckedSub,
+{
+    fn distance(&self, time: &N, hold: &N) -> Option<N> {
+        hold.checked_mul(&time.checked_sub(hold)?)
     }
 }
 
-fn parse_round(input: &str) -> Result<Vec<Round>> {
+impl<N> RaceModel<N> for ChargeRate<N>
+where
+    N: CheckedMul + CheckedSub,
+{
+    fn distance(&self, time: &N, hold: &N) -> Option<N> {
+        self.rate.checked_mul(&LinearCharge.distance(time, hold)?)
+    }
+}
+
+impl<N> RaceModel<N> for CappedSpeed<N>
+where
+    N: Ord + Clone + CheckedMul + CheckedSub,
+{
+    fn distance(&self, time: &N, hold: &N) -> Option<N> {
+        let speed = self.rate.checked_mul(hold)?.min(self.top_speed.clone());
+        speed.checked_mul(&time.checked_sub(hold)?)
+    }
+}
+
+impl<N> RaceModel<N> for DragLimited<N>
+where
+    N: Integer + NumRef + Clone + CheckedMul + CheckedSub,
+    for<'a> &'a N: RefNum<N>,
+{
+    fn distance(&self, time: &N, hold: &N) -> Option<N> {
+        let steps = if self.drag.is_zero() {
+            hold.clone()
+        } else {
+            (&self.rate / &self.drag + N::one()).min(hold.clone())
+        };
+        let two = N::one() + N::one();
+        let lost = self
+            .drag
+            .checked_mul(&steps.checked_mul(&steps)?.checked_sub(&steps)?)?
+            / two;
+        let speed = self.rate.checked_mul(&steps)?.checked_sub(&lost)?;
+
+        speed.checked_mul(&time.checked_sub(hold)?)
+    }
+}
+
+fn brute_force<N>(time: &N, distance: &N) -> Vec<N>
+where
+    N: Roots + NumRef + Clone,
+    for<'a> &'a N: RefNum<N>,
+{
+    let mut hold = N::zero();
+    let mut win_times = vec![];
+
+    while hold < *time {
+        if travel(time, &hold) > *distance {
+            win_times.push(hold.clone());
+        }
+        hold = hold + N::one();
+    }
+
+    win_times
+}
+
+fn travel<N>(time: &N, hold: &N) -> N
+where
+    for<'a> &'a N: RefNum<N>,
+{
+    hold * &(time - hold)
+}
+
+/// Smallest value in `[low, high)` for which `pred` no longer holds, given
+/// that `pred` is true up to some point and false from then on.
+fn partition_point<N>(mut low: N, mut high: N, pred: impl Fn(&N) -> Result<bool>) -> Result<N>
+where
+    N: NumRef + PartialOrd,
+    for<'a> &'a N: RefNum<N>,
+{
+    let two = N::one() + N::one();
+
+    while low < high {
+        let middle = &low + &((&high - &low) / &two);
+        if pred(&middle)? {
+            low = middle + N::one();
+        } else {
+            high = middle;
+        }
+    }
+
+    Ok(low)
+}
+
+/// First hold time at which the model reaches its longest distance.
+pub fn peak_hold<N, M>(model: &M, time: &N) -> Result<N>
+where
+    N: NumRef + Ord + Clone,
+    M: RaceModel<N>,
+    for<'a> &'a N: RefNum<N>,
+{
+    let distance = |hold: &N| model.distance(time, hold).context("Overflow");
+
+    partition_point(N::zero(), time.clone(), |hold| {
+        Ok(distance(hold)? < distance(&(hold + &N::one()))?)
+    })
+}
+
+/// Winning hold interval under any `RaceModel`, found by bisecting for the
+/// peak and then for both edges of the interval around it.
+pub fn solve_model<N, M>(model: &M, time: &N, record: &N) -> Result<Option<RangeInclusive<N>>>
+where
+    N: NumRef + Ord + Clone + CheckedAdd,
+    M: RaceModel<N>,
+    for<'a> &'a N: RefNum<N>,
+{
+    let distance = |hold: &N| model.distance(time, hold).context("Overflow");
+
+    let peak = peak_hold(model, time)?;
+    if distance(&peak)? <= *record {
+        return Ok(None);
+    }
+
+    let end = time.checked_add(&N::one()).context("Overflow")?;
+    let first = partition_point(N::zero(), peak.clone(), |hold| {
+        Ok(distance(hold)? <= *record)
+    })?;
+    let last = partition_point(peak, end, |hold| Ok(distance(hold)? > *record))?;
+
+    Ok(Some(first..=last - N::one()))
+}
+
+/// Exact `[first_win, last_win]` hold times, found from the integer roots
+/// of `hold * (time - hold) > distance`.
+fn solve<N>(time: &N, distance: &N) -> Result<Option<RangeInclusive<N>>>
+where
+    N: Roots + NumRef + Clone + CheckedAdd + CheckedMul + CheckedSub,
+    for<'a> &'a N: RefNum<N>,
+{
+    let two = N::one() + N::one();
+    let square = time.checked_mul(time).context("Overflow")?;
+    let four_distance = distance.checked_mul(&(&two * &two)).context("Overflow")?;
+    let Some(discriminant) = square.checked_sub(&four_distance) else {
+        return Ok(None);
+    };
+
+    let travel = |hold: &N| travel(time, hold);
+    let half = time / &two;
+    let mut first = (time - &discriminant.sqrt()) / &two;
+
+    while travel(&first) <= *distance {
+        first = first + N::one();
+        if first > half {
+            return Ok(None);
+        }
+    }
+    while !first.is_zero() && travel(&(&first - &N::one())) > *distance {
+        first = first - N::one();
+    }
+
+    let last = time - &first;
+    Ok(Some(first..=last))
+}
+
+pub fn parse_round(input: &str) -> Result<Vec<Round>> {
     let (time, distance) = input.split_once('\n').ok_or(anyhow!("No newline"))?;
     time.split_once(':')
         .ok_or(anyhow!("No ':' in time"))?
@@ -142,8 +396,8 @@ fn parse_round(input: &str) -> Result<Vec<Round>> {
                 .split_ascii_whitespace(),
         )
         .map(|(t, d)| {
-            let time = t.trim().parse::<usize>()?;
-            let distance = d.trim().parse::<usize>()?;
+            let time = t.trim().parse::<Number>()?;
+            let distance = d.trim().parse::<Number>()?;
             Ok(Round { time, distance })
         })
         .collect::<Result<_, _>>()
@@ -151,49 +405,272 @@ fn parse_round(input: &str) -> Result<Vec<Round>> {
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
+    fn round(time: u64, distance: u64) -> Round {
+        Round {
+            time: Number::from(time),
+            distance: Number::from(distance),
+        }
+    }
+
+    fn interval(first: u64, last: u64) -> Option<RangeInclusive<Number>> {
+        Some(Number::from(first)..=Number::from(last))
+    }
+
     #[test]
     fn test_day6_part1() {
         let input = include_str!("../example");
         assert_eq!(day6_part1_v1(input).unwrap(), 288);
-        assert_eq!(day6_part1_v2(input).unwrap(), 288);
+        assert_eq!(day6_part1_v2(input).unwrap(), Number::from(288u32));
     }
 
     #[test]
     fn test_day6_part2() {
         let input = include_str!("../example");
         assert_eq!(day6_part2_v1(input).unwrap(), 71503);
-        assert_eq!(day6_part2_v2(input).unwrap(), 71503);
+        assert_eq!(day6_part2_v2(input).unwrap(), Number::from(71503u32));
     }
 
     #[test]
     fn test_parse_round() {
         let input = include_str!("../example");
-        let expected = vec![
-            Round {
-                time: 7,
-                distance: 9,
-            },
-            Round {
-                time: 15,
-                distance: 40,
-            },
-            Round {
-                time: 30,
-                distance: 200,
-            },
-        ];
+        let expected = vec![round(7, 9), round(15, 40), round(30, 200)];
         assert_eq!(parse_round(input).unwrap(), expected);
     }
 
     #[test]
     fn test_get_win_times() {
+        let expected = [2u32, 3, 4, 5].map(Number::from).to_vec();
+        assert_eq!(round(7, 9).get_win_times(), expected);
+    }
+
+    #[test]
+    fn test_win_interval() {
+        assert_eq!(round(30, 200).win_interval().unwrap(), interval(11, 19));
+        assert_eq!(round(10, 25).win_interval().unwrap(), None);
+        assert_eq!(round(10, 24).win_interval().unwrap(), interval(5, 5));
+    }
+
+    #[test]
+    fn test_win_interval_large() {
+        let round = round(u64::MAX, (1u64 << 62) * 3 + 12345);
+        let travel = |hold: &Number| travel(&round.time, hold);
+
+        let interval = round.win_interval().unwrap().unwrap();
+        let before = interval.start() - Number::one();
+        let after = interval.end() + Number::one();
+        assert!(travel(interval.start()) > round.distance);
+        assert!(travel(&before) <= round.distance);
+        assert!(travel(interval.end()) > round.distance);
+        assert!(travel(&after) <= round.distance);
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn test_parse_round_overflow() {
+        let input = "Time: 1234567890123 4567890123456 7890123456789
+Distance: 1 2 3";
+        assert!(day6_part2_v2(input).is_err());
+
+        let round = round(u64::MAX, 1);
         let round = Round {
-            time: 7,
-            distance: 9,
+            time: round.time << 64,
+            distance: round.distance,
         };
-        let expected = vec![2, 3, 4, 5];
-        assert_eq!(round.get_win_times(), expected);
+        assert!(round.ways_to_win().is_err());
+    }
+
+    #[cfg(feature = "bigint")]
+    #[test]
+    fn test_parse_big_round() {
+        let input = "Time: 1234567890123 4567890123456 7890123456789
+Distance: 1234567890123 4567890123456 7890123456789";
+        let rounds = parse_round(&input.replace(' ', "")).unwrap();
+        let round = &rounds[0];
+        let travel = |hold: &Number| travel(&round.time, hold);
+
+        let interval = round.win_interval().unwrap().unwrap();
+        let before = interval.start() - Number::one();
+        assert!(travel(interval.start()) > round.distance);
+        assert!(travel(&before) <= round.distance);
+        assert_eq!(
+            round.ways_to_win().unwrap(),
+            interval.end() - interval.start() + Number::one()
+        );
+    }
+
+    #[test]
+    fn test_win_interval_with_models() {
+        let race = round(30, 200);
+        assert_eq!(
+            race.win_interval_with(&LinearCharge).unwrap(),
+            interval(11, 19)
+        );
+        assert_eq!(
+            race.win_interval_with(&ChargeRate {
+                rate: Number::from(2u32)
+            })
+            .unwrap(),
+            interval(4, 26)
+        );
+        assert_eq!(
+            race.win_interval_with(&CappedSpeed {
+                rate: Number::from(1u32),
+                top_speed: Number::from(10u32)
+            })
+            .unwrap(),
+            None
+        );
+        assert_eq!(
+            race.win_interval_with(&CappedSpeed {
+                rate: Number::from(4u32),
+                top_speed: Number::from(12u32)
+            })
+            .unwrap(),
+            interval(2, 13)
+        );
+        assert_eq!(
+            race.win_interval_with(&DragLimited {
+                rate: Number::from(5u32),
+                drag: Number::from(1u32)
+            })
+            .unwrap(),
+            interval(2, 16)
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn test_models_overflow() {
+        let race = Round {
+            time: Number::MAX,
+            distance: Number::one(),
+        };
+        let rate = Number::from(2u32);
+
+        assert!(race.win_interval_with(&LinearCharge).is_err());
+        assert!(race.ways_to_win_with(&ChargeRate { rate }).is_err());
+        assert!(peak_hold(&LinearCharge, &race.time).is_err());
+
+        // Overflow is an error, not a race that cannot be won.
+        let race = round(u64::MAX, 1);
+        let rate = Number::MAX / 4;
+        assert!(race.win_interval_with(&ChargeRate { rate }).is_err());
+    }
+
+    #[test]
+    fn test_strategy() {
+        let strategy = round(7, 9).strategy().unwrap();
+        assert_eq!(
+            strategy,
+            HoldStrategy {
+                optimal_hold: Number::from(3u32),
+                max_distance: Number::from(12u32),
+                margin: Some(Number::from(3u32)),
+                ways_to_win: Number::from(4u32),
+                sensitivity: Some(Number::from(1u32)),
+            }
+        );
+
+        let strategy = round(30, 200).strategy().unwrap();
+        assert_eq!(strategy.optimal_hold, Number::from(15u32));
+        assert_eq!(strategy.sensitivity, Some(Number::from(4u32)));
+
+        let strategy = round(10, 25).strategy().unwrap();
+        assert_eq!(strategy.max_distance, Number::from(25u32));
+        assert_eq!(strategy.margin, None);
+        assert_eq!(strategy.ways_to_win, Number::zero());
+        assert_eq!(strategy.sensitivity, None);
+    }
+
+    #[test]
+    fn test_strategy_report() {
+        let input = include_str!("../example");
+        let report = strategy_report(&parse_round(input).unwrap()).unwrap();
+        let lines = report.lines().collect_vec();
+
+        assert_eq!(lines.len(), 4);
+        assert_eq!(
+            lines[1].split_ascii_whitespace().collect_vec(),
+            ["1", "7", "9", "3", "12", "3", "4", "1"]
+        );
+        assert_eq!(
+            lines[3].split_ascii_whitespace().collect_vec(),
+            ["3", "30", "200", "15", "225", "25", "9", "4"]
+        );
+    }
+
+    #[cfg(not(feature = "bigint"))]
+    #[test]
+    fn test_strategy_overflow() {
+        let input = "Time: 340282366920938463463374607431768211455
+Distance: 1";
+        let rounds = parse_round(input).unwrap();
+
+        assert!(rounds[0].strategy().is_err());
+        assert!(strategy_report(&rounds).is_err());
+    }
+
+    fn check_model<M: RaceModel<Number>>(model: &M, time: u64, record: u64) {
+        let wins = (0..=time).map(Number::from);
+        let time = Number::from(time);
+        let record = Number::from(record);
+        let wins = wins
+            .filter(|hold| model.distance(&time, hold).unwrap() > record)
+            .collect_vec();
+
+        match solve_model(model, &time, &record).unwrap() {
+            Some(interval) => {
+                assert_eq!(wins.first(), Some(interval.start()));
+                assert_eq!(wins.last(), Some(interval.end()));
+                assert_eq!(
+                    Number::from(wins.len() as u64),
+                    interval.end() - interval.start() + Number::one()
+                );
+            }
+            None => assert!(wins.is_empty()),
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_models_match_brute_force(
+            time in 0..60u64,
+            record in 0..3000u64,
+            rate in 1..6u64,
+            top_speed in 1..40u64,
+            drag in 0..3u64,
+        ) {
+            check_model(&LinearCharge, time, record);
+            check_model(&ChargeRate { rate: Number::from(rate) }, time, record);
+            check_model(
+                &CappedSpeed { rate: Number::from(rate), top_speed: Number::from(top_speed) },
+                time,
+                record,
+            );
+            check_model(
+                &DragLimited { rate: Number::from(rate * 4), drag: Number::from(drag) },
+                time,
+                record,
+            );
+        }
+
+        #[test]
+        fn prop_win_interval_matches_brute_force(time in 0..80u64, distance in 0..1700u64) {
+            let round = round(time, distance);
+            let win_times = round.get_win_times();
+
+            match round.win_interval().unwrap() {
+                Some(interval) => {
+                    prop_assert_eq!(win_times.first(), Some(interval.start()));
+                    prop_assert_eq!(win_times.last(), Some(interval.end()));
+                    prop_assert_eq!(Number::from(win_times.len() as u64), round.ways_to_win().unwrap());
+                }
+                None => prop_assert!(win_times.is_empty()),
+            }
+        }
     }
 }
@@ -1,7 +1,15 @@
-use day06::{day6_part1_v2, day6_part2_v2};
+use day06::{day6_part1_v2, day6_part2_v2, parse_round, strategy_report};
 
 fn main() {
     let input = include_str!("../input");
     println!("Part1: {}", day6_part1_v2(input).unwrap());
     println!("Part2: {}", day6_part2_v2(input).unwrap());
+
+    println!();
+    println!("{}", strategy_report(&parse_round(input).unwrap()).unwrap());
+    println!();
+    println!(
+        "{}",
+        strategy_report(&parse_round(&input.replace(' ', "")).unwrap()).unwrap()
+    );
 }
@@ -1,71 +1,109 @@
-use std::collections::HashMap;
+use std::{
+    cmp::Ordering,
+    fmt::{self, Display, Formatter},
+    iter::once,
+};
 
-use anyhow::{anyhow, Ok, Result};
+use anyhow::{anyhow, bail, Ok, Result};
 use itertools::Itertools;
 #[allow(unused_imports)]
 use tap::Tap;
 
 pub fn part1(input: &str) -> Result<usize> {
-    let result = input
-        .lines()
-        .map(|line| {
-            let (head, tail) = line
-                .split_once(' ')
-                .ok_or(anyhow!("Parse fail: {}", line))?;
-            let bet_number = tail.parse::<usize>()?;
-            let labels: [Label; 5] = head
-                .chars()
-                .map(|c| Label::from_char_part1(&c))
-                .process_results(|iter| iter.collect_vec())?
-                .try_into()
-                .map_err(|_| anyhow!("Parse Labels Fail:{}", line))?;
-
-            let card_type = CardType::from_labels_part1(labels);
-
-            Ok((bet_number, card_type))
-        })
-        .process_results(|iter| {
-            iter.sorted_by_key(|card| card.1)
-                .enumerate()
-                .map(|(round, card)| (round + 1) * card.0)
-                .sum()
-        })?;
+    total_winnings(input, &Ruleset::standard())
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    total_winnings(input, &Ruleset::jokers())
+}
+
+pub fn total_winnings(input: &str, ruleset: &Ruleset) -> Result<usize> {
+    let result = rank_hands(input, ruleset)?
+        .iter()
+        .map(|hand| hand.winnings)
+        .sum();
 
     Ok(result)
 }
 
-pub fn part2(input: &str) -> Result<usize> {
+#[derive(Debug, PartialEq, Eq)]
+pub struct RankedHand {
+    pub labels: Vec<Label>,
+    /// The hand after wildcards took their best labels.
+    pub resolved: Vec<Label>,
+    pub category: String,
+    pub rank: usize,
+    pub bid: usize,
+    pub winnings: usize,
+}
+
+/// Every hand in `input`, weakest first.
+pub fn rank_hands(input: &str, ruleset: &Ruleset) -> Result<Vec<RankedHand>> {
     let result = input
         .lines()
         .map(|line| {
-            let (head, tail) = line
-                .split_once(' ')
-                .ok_or(anyhow!("Parse fail: {}", line))?;
-            let bet_number = tail.parse::<usize>()?;
-            let labels: [Label; 5] = head
-                .chars()
-                .map(|c| Label::from_char_part2(&c))
-                .process_results(|iter| iter.collect_vec())?
-                .try_into()
-                .map_err(|_| anyhow!("Parse Labels Fail:{}", line))?;
-
-            let card_type = CardType::from_labels_part2(labels);
-
-            Ok((bet_number, card_type))
+            let (labels, bet_number) = parse_hand(line, ruleset.hand_size)?;
+
+            Ok((bet_number, ruleset.score_hand(&labels), labels))
         })
         .process_results(|iter| {
-            iter.sorted_by_key(|card| card.1)
+            iter.sorted_by(|a, b| a.1.cmp(&b.1))
                 .enumerate()
-                .map(|(round, card)| (round + 1) * card.0)
-                .sum()
+                .map(|(round, (bid, (category, _), labels))| RankedHand {
+                    resolved: ruleset.resolve_wildcards(&labels),
+                    labels,
+                    category: ruleset.categories[category].name.clone(),
+                    rank: round + 1,
+                    bid,
+                    winnings: (round + 1) * bid,
+                })
+                .collect()
         })?;
 
     Ok(result)
 }
 
+pub fn ranking_report(hands: &[RankedHand]) -> String {
+    let header = format!(
+        "{:>5} {:>8} {:>8} {:<16} {:>6} {:>10}",
+        "Rank", "Hand", "Resolved", "Category", "Bid", "Winnings"
+    );
+
+    once(header)
+        .chain(hands.iter().map(|hand| {
+            format!(
+                "{:>5} {:>8} {:>8} {:<16} {:>6} {:>10}",
+                hand.rank,
+                hand.labels.iter().join(""),
+                hand.resolved.iter().join(""),
+                hand.category,
+                hand.bid,
+                hand.winnings
+            )
+        }))
+        .join("\n")
+}
+
+pub fn parse_labels(s: &str) -> Result<Vec<Label>> {
+    s.chars().map(|c| Label::from_char(&c)).collect()
+}
+
+fn parse_hand(line: &str, hand_size: usize) -> Result<(Vec<Label>, usize)> {
+    let (head, tail) = line
+        .split_once(' ')
+        .ok_or(anyhow!("Parse fail: {}", line))?;
+    let bet_number = tail.parse::<usize>()?;
+    let labels = parse_labels(head)?;
+
+    if labels.len() != hand_size {
+        bail!("Parse Labels Fail:{}", line);
+    }
+
+    Ok((labels, bet_number))
+}
+
 #[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Hash)]
-enum Label {
-    Jocker,
+pub enum Label {
     Two,
     Three,
     Four,
@@ -81,8 +119,29 @@ enum Label {
     Ace,
 }
 
+impl Display for Label {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let c = match self {
+            Label::Two => '2',
+            Label::Three => '3',
+            Label::Four => '4',
+            Label::Five => '5',
+            Label::Six => '6',
+            Label::Seven => '7',
+            Label::Eight => '8',
+            Label::Nine => '9',
+            Label::Ten => 'T',
+            Label::Jack => 'J',
+            Label::Queen => 'Q',
+            Label::King => 'K',
+            Label::Ace => 'A',
+        };
+        write!(f, "{}", c)
+    }
+}
+
 impl Label {
-    fn from_char_part1(c: &char) -> Result<Self> {
+    fn from_char(c: &char) -> Result<Self> {
         match c {
             '2' => Ok(Label::Two),
             '3' => Ok(Label::Three),
@@ -100,104 +159,408 @@ impl Label {
             _ => Err(anyhow::anyhow!("Invalid label: {}", c)),
         }
     }
+}
 
-    fn from_char_part2(c: &char) -> Result<Self> {
-        match c {
-            '2' => Ok(Label::Two),
-            '3' => Ok(Label::Three),
-            '4' => Ok(Label::Four),
-            '5' => Ok(Label::Five),
-            '6' => Ok(Label::Six),
-            '7' => Ok(Label::Seven),
-            '8' => Ok(Label::Eight),
-            '9' => Ok(Label::Nine),
-            'T' => Ok(Label::Ten),
-            'J' => Ok(Label::Jocker),
-            'Q' => Ok(Label::Queen),
-            'K' => Ok(Label::King),
-            'A' => Ok(Label::Ace),
-            _ => Err(anyhow::anyhow!("Invalid label: {}", c)),
+/// Why one hand beats another.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Reason {
+    Category {
+        stronger: String,
+        weaker: String,
+    },
+    TieBreak {
+        category: String,
+        position: usize,
+        stronger: Label,
+        weaker: Label,
+    },
+    Identical,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub struct Explanation {
+    /// How the first hand compares to the second.
+    pub ordering: Ordering,
+    pub reason: Reason,
+}
+
+impl Display for Explanation {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let winner = match self.ordering {
+            Ordering::Greater => "first hand wins",
+            Ordering::Less => "second hand wins",
+            Ordering::Equal => "hands tie",
+        };
+
+        match &self.reason {
+            Reason::Category { stronger, weaker } => {
+                write!(f, "{}: {} beats {}", winner, stronger, weaker)
+            }
+            Reason::TieBreak {
+                category,
+                position,
+                stronger,
+                weaker,
+            } => write!(
+                f,
+                "{}: both are {}, card {} decides, {} beats {}",
+                winner,
+                category,
+                position + 1,
+                stronger,
+                weaker
+            ),
+            Reason::Identical => write!(f, "{}: same category and same tie-break labels", winner),
+        }
+    }
+}
+
+/// Sizes of the groups of equal labels in a hand, largest first.
+fn groups(labels: &[Label]) -> Vec<usize> {
+    let counts = labels.iter().fold([0; 13], |mut counts, label| {
+        counts[*label as usize] += 1;
+        counts
+    });
+
+    counts
+        .into_iter()
+        .filter(|count| *count > 0)
+        .sorted_unstable_by(|a, b| b.cmp(a))
+        .collect()
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Category {
+    pub name: String,
+    /// Groups the hand has to contain, largest first: `[3, 2]` is a full
+    /// house, `[2, 2, 2]` is three pair.
+    pub signature: Vec<usize>,
+}
+
+impl Category {
+    pub fn new(name: &str, signature: &[usize]) -> Self {
+        Category {
+            name: name.to_string(),
+            signature: signature.to_vec(),
         }
     }
+
+    fn fits(&self, groups: &[usize]) -> bool {
+        self.signature.len() <= groups.len()
+            && self
+                .signature
+                .iter()
+                .zip(groups)
+                .all(|(needed, group)| needed <= group)
+    }
 }
 
-#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
-enum CardType {
-    HighCard([Label; 5]),
-    OnePair([Label; 5]),
-    TwoPair([Label; 5]),
-    ThreeKind([Label; 5]),
-    FullHouse([Label; 5]),
-    FourKind([Label; 5]),
-    FiveKind([Label; 5]),
+/// Everything that differs between Camel Cards variants. Only valid
+/// rulesets can be built, so scoring never has to check it again.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ruleset {
+    hand_size: usize,
+    /// Labels from weakest to strongest.
+    order: Vec<Label>,
+    /// Labels that stand in for whichever label makes the best category.
+    wildcards: Vec<Label>,
+    /// Hand positions compared in turn when two hands share a category.
+    tie_break: Vec<usize>,
+    /// Categories from weakest to strongest. A hand belongs to the
+    /// strongest category whose signature it contains.
+    categories: Vec<Category>,
 }
 
-impl CardType {
-    fn from_labels_part1(labels: [Label; 5]) -> Self {
-        let map = labels.iter().fold(HashMap::new(), |mut map, label| {
-            *map.entry(label).or_insert(0) += 1;
-            map
+impl Ruleset {
+    pub fn new(
+        hand_size: usize,
+        order: Vec<Label>,
+        wildcards: Vec<Label>,
+        tie_break: Vec<usize>,
+        categories: Vec<Category>,
+    ) -> Result<Self> {
+        let ruleset = Ruleset {
+            hand_size,
+            order,
+            wildcards,
+            tie_break,
+            categories,
+        };
+        ruleset.validate()?;
+
+        Ok(ruleset)
+    }
+
+    pub fn standard() -> Self {
+        Ruleset::default_order(
+            5,
+            vec![
+                Category::new("High Card", &[1]),
+                Category::new("One Pair", &[2]),
+                Category::new("Two Pair", &[2, 2]),
+                Category::new("Three of a Kind", &[3]),
+                Category::new("Full House", &[3, 2]),
+                Category::new("Four of a Kind", &[4]),
+                Category::new("Five of a Kind", &[5]),
+            ],
+        )
+    }
+
+    /// `J` cards are jokers: wild, and the weakest label on a tie.
+    pub fn jokers() -> Self {
+        let mut ruleset = Ruleset::standard();
+        ruleset.order.retain(|label| *label != Label::Jack);
+        ruleset.order.insert(0, Label::Jack);
+        ruleset.wildcards = vec![Label::Jack];
+        ruleset
+    }
+
+    /// Standard labels and left-to-right tie-break for hands of any size.
+    pub fn with_categories(hand_size: usize, categories: Vec<Category>) -> Result<Self> {
+        let ruleset = Ruleset::default_order(hand_size, categories);
+        ruleset.validate()?;
+
+        Ok(ruleset)
+    }
+
+    fn default_order(hand_size: usize, categories: Vec<Category>) -> Self {
+        Ruleset {
+            hand_size,
+            order: vec![
+                Label::Two,
+                Label::Three,
+                Label::Four,
+                Label::Five,
+                Label::Six,
+                Label::Seven,
+                Label::Eight,
+                Label::Nine,
+                Label::Ten,
+                Label::Jack,
+                Label::Queen,
+                Label::King,
+                Label::Ace,
+            ],
+            wildcards: vec![],
+            tie_break: (0..hand_size).collect(),
+            categories,
+        }
+    }
+
+    pub fn hand_size(&self) -> usize {
+        self.hand_size
+    }
+
+    pub fn order(&self) -> &[Label] {
+        &self.order
+    }
+
+    pub fn wildcards(&self) -> &[Label] {
+        &self.wildcards
+    }
+
+    pub fn tie_break(&self) -> &[usize] {
+        &self.tie_break
+    }
+
+    pub fn categories(&self) -> &[Category] {
+        &self.categories
+    }
+
+    fn validate(&self) -> Result<()> {
+        if self.order.len() != 13 || !self.order.iter().all_unique() {
+            bail!("Order Must List Every Label Once");
+        }
+        if let Some(wildcard) = self
+            .wildcards
+            .iter()
+            .find(|wildcard| !self.order.contains(wildcard))
+        {
+            bail!("Wildcard {} Missing From Order", wildcard);
+        }
+
+        if let Some(position) = self
+            .tie_break
+            .iter()
+            .find(|position| **position >= self.hand_size)
+        {
+            bail!("Tie-break Position {} Outside Hand", position);
+        }
+
+        match self.categories.first() {
+            Some(weakest) if weakest.fits(&[1]) => {}
+            _ => bail!("Weakest Category Must Fit Every Hand"),
+        }
+
+        for category in &self.categories {
+            if category.signature.iter().sum::<usize>() > self.hand_size
+                || category.signature.contains(&0)
+                || category.signature.windows(2).any(|w| w[0] < w[1])
+            {
+                bail!("Invalid Signature For {}", category.name);
+            }
+        }
+
+        // A stronger category contained in a weaker one would always win,
+        // so the weaker one could never be reached.
+        for (weaker, stronger) in self.categories.iter().tuple_combinations() {
+            if stronger.fits(&weaker.signature) {
+                bail!(
+                    "{} Can Never Be Reached Above {}",
+                    weaker.name,
+                    stronger.name
+                );
+            }
+        }
+
+        Ok(())
+    }
+
+    fn strength(&self, label: &Label) -> usize {
+        self.order
+            .iter()
+            .position(|l| l == label)
+            .unwrap_or_default()
+    }
+
+    fn rank(&self, labels: &[Label]) -> usize {
+        let groups = groups(labels);
+
+        self.categories
+            .iter()
+            .rposition(|category| category.fits(&groups))
+            .unwrap_or_default()
+    }
+
+    /// The hand with every wildcard replaced so that its category is as
+    /// strong as possible. Only the group sizes matter for the category, so
+    /// each wildcard either joins a label already in the hand or becomes the
+    /// strongest label not in it yet.
+    pub fn resolve(&self, labels: &[Label]) -> Result<Vec<Label>> {
+        self.check(labels)?;
+        Ok(self.resolve_wildcards(labels))
+    }
+
+    fn resolve_wildcards(&self, labels: &[Label]) -> Vec<Label> {
+        let mut best = None;
+        self.search(labels.to_vec(), 0, &mut best);
+        best.map_or(labels.to_vec(), |(_, labels)| labels)
+    }
+
+    fn search(&self, labels: Vec<Label>, from: usize, best: &mut Option<(usize, Vec<Label>)>) {
+        let Some(index) = (from..labels.len()).find(|i| self.wildcards.contains(&labels[*i]))
+        else {
+            let rank = self.rank(&labels);
+            if best.as_ref().is_none_or(|(best_rank, _)| rank > *best_rank) {
+                *best = Some((rank, labels));
+            }
+            return;
+        };
+
+        let present = labels.iter().fold(vec![], |mut present, label| {
+            if !self.wildcards.contains(label) && !present.contains(label) {
+                present.push(*label);
+            }
+            present
         });
+        let fresh = self
+            .order
+            .iter()
+            .rev()
+            .find(|label| !self.wildcards.contains(label) && !present.contains(label));
+
+        for candidate in present.iter().chain(fresh) {
+            let mut labels = labels.clone();
+            labels[index] = *candidate;
+            self.search(labels, index + 1, best);
+        }
+    }
 
-        let set = map.values().sorted().collect_vec();
+    pub fn category(&self, labels: &[Label]) -> Result<&Category> {
+        self.check(labels)?;
+        Ok(&self.categories[self.rank(&self.resolve_wildcards(labels))])
+    }
 
-        match set.as_slice() {
-            [5] => CardType::FiveKind(labels),
-            [1, 4] => CardType::FourKind(labels),
-            [2, 3] => CardType::FullHouse(labels),
-            [1, 1, 3] => CardType::ThreeKind(labels),
-            [1, 2, 2] => CardType::TwoPair(labels),
-            [1, 1, 1, 2] => CardType::OnePair(labels),
-            _ => CardType::HighCard(labels),
+    /// Hands compare by category first, then by label strength at each
+    /// tie-break position.
+    pub fn score(&self, labels: &[Label]) -> Result<(usize, Vec<usize>)> {
+        self.check(labels)?;
+        Ok(self.score_hand(labels))
+    }
+
+    /// Errors unless `labels` is a whole hand, so the unchecked helpers
+    /// below can index freely.
+    fn check(&self, labels: &[Label]) -> Result<()> {
+        if labels.len() != self.hand_size {
+            bail!("Hand Must Have {} Cards", self.hand_size);
         }
+
+        Ok(())
     }
 
-    /// needed debug
-    fn from_labels_part2(labels: [Label; 5]) -> Self {
-        let (map, jockers) =
-            labels
-                .iter()
-                .fold((HashMap::new(), vec![]), |(mut map, mut jockers), label| {
-                    if *label == Label::Jocker {
-                        jockers.push(label);
-                    } else {
-                        *map.entry(label).or_insert(0) += 1;
-                    }
-
-                    (map, jockers)
-                });
-
-        let set = map.values().sorted().collect_vec();
-        let jocker_count = jockers.len();
-
-        match (set.as_slice(), jocker_count) {
-            (l, 0) => match l {
-                [5] => CardType::FiveKind(labels),
-                [1, 4] => CardType::FourKind(labels),
-                [2, 3] => CardType::FullHouse(labels),
-                [1, 1, 3] => CardType::ThreeKind(labels),
-                [1, 2, 2] => CardType::TwoPair(labels),
-                [1, 1, 1, 2] => CardType::OnePair(labels),
-                _ => CardType::HighCard(labels),
-            },
-            (l, 1) => match l {
-                [4] => CardType::FiveKind(labels),
-                [1, 3] => CardType::FourKind(labels),
-                [2, 2] => CardType::FullHouse(labels),
-                [1, 1, 2] => CardType::ThreeKind(labels),
-                _ => CardType::OnePair(labels),
-            },
-            (l, 2) => match l {
-                [3] => CardType::FiveKind(labels),
-                [1, 2] => CardType::FourKind(labels),
-                _ => CardType::ThreeKind(labels),
-            },
-            (l, 3) => match l {
-                [2] => CardType::FiveKind(labels),
-                _ => CardType::FourKind(labels),
-            },
-            _ => CardType::FiveKind(labels),
+    fn score_hand(&self, labels: &[Label]) -> (usize, Vec<usize>) {
+        let tie_break = self
+            .tie_break
+            .iter()
+            .map(|position| self.strength(&labels[*position]))
+            .collect();
+
+        (self.rank(&self.resolve_wildcards(labels)), tie_break)
+    }
+
+    pub fn explain(&self, first: &[Label], second: &[Label]) -> Result<Explanation> {
+        self.check(first)?;
+        self.check(second)?;
+
+        let (first_rank, first_tie_break) = self.score_hand(first);
+        let (second_rank, second_tie_break) = self.score_hand(second);
+        let name = |rank: usize| self.categories[rank].name.clone();
+
+        if first_rank != second_rank {
+            let (stronger, weaker) = if first_rank > second_rank {
+                (first_rank, second_rank)
+            } else {
+                (second_rank, first_rank)
+            };
+            return Ok(Explanation {
+                ordering: first_rank.cmp(&second_rank),
+                reason: Reason::Category {
+                    stronger: name(stronger),
+                    weaker: name(weaker),
+                },
+            });
         }
+
+        let decider = self
+            .tie_break
+            .iter()
+            .zip(first_tie_break.iter().zip(&second_tie_break))
+            .find(|(_, (a, b))| a != b);
+
+        let explanation = match decider {
+            Some((position, (a, b))) => {
+                let (stronger, weaker) = if a > b {
+                    (first[*position], second[*position])
+                } else {
+                    (second[*position], first[*position])
+                };
+                Explanation {
+                    ordering: a.cmp(b),
+                    reason: Reason::TieBreak {
+                        category: name(first_rank),
+                        position: *position,
+                        stronger,
+                        weaker,
+                    },
+                }
+            }
+            None => Explanation {
+                ordering: Ordering::Equal,
+                reason: Reason::Identical,
+            },
+        };
+
+        Ok(explanation)
     }
 }
 
@@ -205,9 +568,16 @@ impl CardType {
 mod tests {
     use itertools::Itertools;
 
-    use crate::{part1, part2, CardType, Label};
+    use std::collections::HashMap;
 
-    use super::{CardType::*, Label::*};
+    use std::cmp::Ordering;
+
+    use crate::{
+        parse_hand, parse_labels, part1, part2, rank_hands, ranking_report, total_winnings,
+        Category, Label, Reason, Ruleset,
+    };
+
+    use super::Label::*;
 
     #[test]
     fn test_part_1() {
@@ -221,6 +591,96 @@ mod tests {
         assert_eq!(part2(input).unwrap(), 5905);
     }
 
+    #[test]
+    fn test_rank_hands() {
+        let input = include_str!("../example");
+        let hands = rank_hands(input, &Ruleset::jokers()).unwrap();
+
+        let summary = hands
+            .iter()
+            .map(|hand| {
+                (
+                    hand.labels.iter().join(""),
+                    hand.resolved.iter().join(""),
+                    hand.category.as_str(),
+                    hand.rank,
+                    hand.winnings,
+                )
+            })
+            .collect_vec();
+        assert_eq!(
+            summary,
+            [
+                ("32T3K".to_string(), "32T3K".to_string(), "One Pair", 1, 765),
+                ("KK677".to_string(), "KK677".to_string(), "Two Pair", 2, 56),
+                (
+                    "T55J5".to_string(),
+                    "T5555".to_string(),
+                    "Four of a Kind",
+                    3,
+                    2052
+                ),
+                (
+                    "QQQJA".to_string(),
+                    "QQQQA".to_string(),
+                    "Four of a Kind",
+                    4,
+                    1932
+                ),
+                (
+                    "KTJJT".to_string(),
+                    "KTTTT".to_string(),
+                    "Four of a Kind",
+                    5,
+                    1100
+                ),
+            ]
+        );
+
+        let report = ranking_report(&hands);
+        assert_eq!(report.lines().count(), 6);
+        assert!(report.lines().nth(5).unwrap().contains("KTTTT"));
+    }
+
+    #[test]
+    fn test_explain() {
+        let ruleset = Ruleset::jokers();
+        let hand = |s: &str| parse_labels(s).unwrap();
+
+        let explanation = ruleset.explain(&hand("KTJJT"), &hand("KK677")).unwrap();
+        assert_eq!(explanation.ordering, Ordering::Greater);
+        assert_eq!(
+            explanation.reason,
+            Reason::Category {
+                stronger: "Four of a Kind".to_string(),
+                weaker: "Two Pair".to_string()
+            }
+        );
+
+        let explanation = ruleset.explain(&hand("QQQJA"), &hand("KTJJT")).unwrap();
+        assert_eq!(explanation.ordering, Ordering::Less);
+        assert_eq!(
+            explanation.reason,
+            Reason::TieBreak {
+                category: "Four of a Kind".to_string(),
+                position: 0,
+                stronger: King,
+                weaker: Queen
+            }
+        );
+        assert_eq!(
+            explanation.to_string(),
+            "second hand wins: both are Four of a Kind, card 1 decides, K beats Q"
+        );
+
+        let explanation = ruleset.explain(&hand("T55J5"), &hand("T55J5")).unwrap();
+        assert_eq!(explanation.ordering, Ordering::Equal);
+        assert_eq!(explanation.reason, Reason::Identical);
+
+        assert!(ruleset.explain(&hand("AK"), &hand("QQ")).is_err());
+        assert!(ruleset.explain(&hand("KTJJT"), &hand("KK6777")).is_err());
+    }
+
     #[test]
     fn test_compare_label() {
         assert!(Ace > King);
@@ -245,31 +705,48 @@ mod tests {
         // KTJJT 220
         // QQQJA 483
 
-        let input = [
-            OnePair([Three, Two, Ten, Three, King]),
-            ThreeKind([Ten, Five, Five, Jack, Five]),
-            TwoPair([King, King, Six, Seven, Seven]),
-            TwoPair([King, Ten, Jack, Jack, Ten]),
-            ThreeKind([Queen, Queen, Queen, Jack, Ace]),
-        ]
-        .iter()
-        .enumerate()
-        .sorted_by_key(|card| card.1)
-        .map(|card| card.0)
-        .collect_vec();
+        let ruleset = Ruleset::standard();
+        let input = include_str!("../example")
+            .lines()
+            .map(|line| parse_hand(line, 5).unwrap().0)
+            .enumerate()
+            .sorted_by_key(|(_, labels)| ruleset.score(labels).unwrap())
+            .map(|card| card.0)
+            .collect_vec();
 
         assert_eq!(input, [0, 3, 2, 1, 4]);
     }
 
     #[test]
     fn test_parse_label() {
-        assert_eq!(Label::from_char_part1(&'2').unwrap(), Two);
-        assert_eq!(Label::from_char_part1(&'3').unwrap(), Three);
-        assert_eq!(Label::from_char_part1(&'J').unwrap(), Jack);
+        assert_eq!(Label::from_char(&'2').unwrap(), Two);
+        assert_eq!(Label::from_char(&'3').unwrap(), Three);
+        assert_eq!(Label::from_char(&'J').unwrap(), Jack);
+        assert!(Label::from_char(&'X').is_err());
+    }
+
+    #[test]
+    fn test_ruleset_tie_break() {
+        let standard = Ruleset::standard();
+        let jokers = Ruleset::jokers();
 
-        assert_eq!(Label::from_char_part2(&'2').unwrap(), Two);
-        assert_eq!(Label::from_char_part2(&'3').unwrap(), Three);
-        assert_eq!(Label::from_char_part2(&'J').unwrap(), Jocker);
+        assert!(
+            standard.score(&[Jack, Two, Two, Two, Two]).unwrap()
+                > standard.score(&[Two, Two, Two, Two, Ten]).unwrap()
+        );
+        assert!(
+            jokers.score(&[Jack, Two, Two, Two, Two]).unwrap()
+                < jokers.score(&[Two, Two, Two, Two, Two]).unwrap()
+        );
+
+        let reversed = Ruleset {
+            tie_break: (0..5).rev().collect(),
+            ..Ruleset::standard()
+        };
+        assert!(
+            reversed.score(&[Ace, Two, Three, Four, Five]).unwrap()
+                < reversed.score(&[Two, Three, Four, Five, Six]).unwrap()
+        );
     }
 
     #[test]
@@ -280,53 +757,189 @@ mod tests {
         // KTJJT 220
         // QQQJA 483
 
+        let ruleset = Ruleset::jokers();
+        let name = |labels: &[Label]| ruleset.category(labels).unwrap().name.clone();
+
+        assert_eq!(name(&[Three, Two, Ten, Three, King]), "One Pair");
+        assert_eq!(name(&[Ten, Five, Five, Jack, Five]), "Four of a Kind");
+        assert_eq!(name(&[King, King, Six, Seven, Seven]), "Two Pair");
+        assert_eq!(name(&[King, Ten, Jack, Jack, Ten]), "Four of a Kind");
+        assert_eq!(name(&[Queen, Queen, Queen, Jack, Ace]), "Four of a Kind");
+        assert_eq!(name(&[Jack, Jack, Jack, Jack, Jack]), "Five of a Kind");
+
         assert_eq!(
-            CardType::from_labels_part2([Three, Two, Ten, Three, King]),
-            OnePair([Three, Two, Ten, Three, King])
+            Ruleset::standard()
+                .category(&[King, Ten, Jack, Jack, Ten])
+                .unwrap()
+                .name,
+            "Two Pair"
         );
+    }
+
+    #[test]
+    fn test_resolve_wildcards() {
+        let ruleset = Ruleset::jokers();
 
         assert_eq!(
-            CardType::from_labels_part2([Ten, Five, Five, Jocker, Five]),
-            FourKind([Ten, Five, Five, Jocker, Five])
+            ruleset.resolve(&[King, Ten, Jack, Jack, Ten]).unwrap(),
+            [King, Ten, Ten, Ten, Ten]
         );
-
         assert_eq!(
-            CardType::from_labels_part2([King, King, Six, Seven, Seven]),
-            TwoPair([King, King, Six, Seven, Seven])
+            ruleset.resolve(&[Jack, Jack, Jack, Jack, Jack]).unwrap(),
+            [Ace, Ace, Ace, Ace, Ace]
         );
+        assert_eq!(
+            ruleset.resolve(&[Two, Three, Four, Five, Six]).unwrap(),
+            [Two, Three, Four, Five, Six]
+        );
+    }
 
+    fn brute_force(ruleset: &Ruleset, labels: &[Label]) -> usize {
+        match labels
+            .iter()
+            .position(|label| ruleset.wildcards.contains(label))
+        {
+            None => ruleset.rank(labels),
+            Some(index) => ruleset
+                .order
+                .iter()
+                .filter(|label| !ruleset.wildcards.contains(label))
+                .map(|label| {
+                    let mut labels = labels.to_vec();
+                    labels[index] = *label;
+                    brute_force(ruleset, &labels)
+                })
+                .max()
+                .unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_resolve_matches_brute_force() {
+        let ruleset = Ruleset::jokers();
+        let mut cache = HashMap::new();
+
+        for hand in (0..5)
+            .map(|_| ruleset.order.iter().copied())
+            .multi_cartesian_product()
+        {
+            let expected = *cache
+                .entry(hand.iter().sorted().copied().collect_vec())
+                .or_insert_with(|| brute_force(&ruleset, &hand));
+
+            assert_eq!(ruleset.score(&hand).unwrap().0, expected, "{:?}", hand);
+        }
+    }
+
+    fn variants() -> Ruleset {
+        Ruleset::with_categories(
+            6,
+            vec![
+                Category::new("High Card", &[1]),
+                Category::new("One Pair", &[2]),
+                Category::new("Two Pair", &[2, 2]),
+                Category::new("Three of a Kind", &[3]),
+                Category::new("Three Pair", &[2, 2, 2]),
+                Category::new("Full House", &[3, 2]),
+                Category::new("Four of a Kind", &[4]),
+            ],
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_variable_hand_size() {
+        let ruleset = variants();
+        assert!(ruleset.validate().is_ok());
+
+        let name = |labels: &[Label]| ruleset.category(labels).unwrap().name.clone();
+        assert_eq!(name(&[Two, Two, Three, Three, Four, Four]), "Three Pair");
+        assert_eq!(name(&[Two, Two, Two, Three, Three, Four]), "Full House");
+        assert_eq!(name(&[Two, Two, Two, Three, Three, Three]), "Full House");
+        assert_eq!(name(&[Two, Two, Two, Two, Two, Four]), "Four of a Kind");
+        assert_eq!(name(&[Two, Two, Three, Three, Four, Five]), "Two Pair");
+
+        let input = "223344 10
+222334 20
+234567 30";
+        assert_eq!(
+            total_winnings(input, &ruleset).unwrap(),
+            10 * 2 + 20 * 3 + 30
+        );
+        assert!(total_winnings("22334 10", &ruleset).is_err());
+
+        let three_cards = Ruleset::with_categories(
+            3,
+            vec![
+                Category::new("High Card", &[1]),
+                Category::new("Pair", &[2]),
+                Category::new("Trips", &[3]),
+            ],
+        )
+        .unwrap();
         assert_eq!(
-            CardType::from_labels_part2([King, Ten, Jocker, Jocker, Ten]),
-            FourKind([King, Ten, Jocker, Jocker, Ten])
+            total_winnings("AKQ 1\n222 2\n33A 3", &three_cards).unwrap(),
+            1 + 2 * 3 + 3 * 2
         );
 
+        let mut seven_cards = variants();
+        seven_cards.hand_size = 7;
+        seven_cards.tie_break = (0..7).collect();
+        seven_cards.wildcards = vec![Jack];
         assert_eq!(
-            CardType::from_labels_part2([Queen, Queen, Queen, Jocker, Ace]),
-            FourKind([Queen, Queen, Queen, Jocker, Ace])
+            seven_cards
+                .category(&[Two, Two, Three, Three, Four, Jack, Ace])
+                .unwrap()
+                .name,
+            "Full House"
         );
     }
 
-    // #[test]
-    // fn test_parse_card_type() {
-    //     assert_eq!(
-    //         "32T3K".parse::<CardType>().unwrap(),
-    //         OnePair([Three, Two, Ten, Three, King])
-    //     );
-    //     assert_eq!(
-    //         "76584".parse::<CardType>().unwrap(),
-    //         HighCard([Seven, Six, Five, Eight, Four])
-    //     );
-    //     assert_eq!(
-    //         "KK677".parse::<CardType>().unwrap(),
-    //         TwoPair([King, King, Six, Seven, Seven])
-    //     );
-    //     assert_eq!(
-    //         "KTJJT".parse::<CardType>().unwrap(),
-    //         TwoPair([King, Ten, Jack, Jack, Ten])
-    //     );
-    //     assert_eq!(
-    //         "QQQJA".parse::<CardType>().unwrap(),
-    //         ThreeKind([Queen, Queen, Queen, Jack, Ace])
-    //     );
-    // }
+    #[test]
+    fn test_invalid_ruleset() {
+        let mut ruleset = variants();
+        ruleset.categories.swap(3, 4);
+        assert!(ruleset.validate().is_ok());
+
+        let mut ruleset = variants();
+        ruleset.categories.swap(2, 4);
+        assert!(ruleset.validate().is_err());
+
+        let mut ruleset = variants();
+        ruleset.categories.remove(0);
+        assert!(ruleset.validate().is_err());
+
+        let mut ruleset = variants();
+        ruleset
+            .categories
+            .push(Category::new("Seven of a Kind", &[7]));
+        assert!(ruleset.validate().is_err());
+
+        let mut ruleset = variants();
+        ruleset.tie_break = vec![6];
+        assert!(ruleset.validate().is_err());
+
+        assert!(Ruleset::standard().validate().is_ok());
+        assert!(Ruleset::jokers().validate().is_ok());
+
+        // Invalid rulesets cannot be built at all.
+        let standard = Ruleset::standard();
+        let categories = standard.categories().to_vec();
+        let build = |order: Vec<Label>, wildcards: Vec<Label>, tie_break: Vec<usize>| {
+            Ruleset::new(5, order, wildcards, tie_break, categories.clone())
+        };
+        assert!(build(standard.order().to_vec(), vec![Jack], vec![0, 1]).is_ok());
+        assert!(build(standard.order().to_vec(), vec![], vec![5]).is_err());
+        assert!(build(standard.order()[1..].to_vec(), vec![], vec![0]).is_err());
+        assert!(build([standard.order(), &[Ace]].concat(), vec![], vec![0]).is_err());
+        assert!(build(vec![Two; 13], vec![], vec![0]).is_err());
+        assert!(Ruleset::with_categories(5, vec![]).is_err());
+
+        // The public entry points refuse a partial hand instead of indexing
+        // past it.
+        let hand = [Two, Two, Three, Three, Four, Four];
+        let ruleset = variants();
+        assert!(ruleset.score(&hand[..2]).is_err());
+        assert!(ruleset.category(&hand[..2]).is_err());
+    }
 }
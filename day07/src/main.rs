@@ -1,8 +1,35 @@
-use day07::{part1, part2};
+use std::env;
+
+use day07::{parse_labels, part1, part2, rank_hands, ranking_report, Ruleset};
 
 fn main() {
     let input = include_str!("../input");
+    let (flags, args): (Vec<_>, Vec<_>) =
+        env::args().skip(1).partition(|arg| arg.starts_with("--"));
+    let ruleset = if flags.iter().any(|flag| flag == "--jokers") {
+        Ruleset::jokers()
+    } else {
+        Ruleset::standard()
+    };
 
-    println!("Part 1: {}", part1(input).unwrap());
-    println!("Part 2: {}", part2(input).unwrap());
+    match args.first().map(String::as_str) {
+        Some("explain") => {
+            let hands = rank_hands(input, &ruleset).unwrap();
+            println!("{}", ranking_report(&hands));
+        }
+        Some("compare") => {
+            let first = args.get(1).expect("compare needs two hands");
+            let second = args.get(2).expect("compare needs two hands");
+            let first = parse_labels(first).unwrap();
+            let second = parse_labels(second).unwrap();
+            match ruleset.explain(&first, &second) {
+                Ok(explanation) => println!("{}", explanation),
+                Err(error) => eprintln!("{}", error),
+            }
+        }
+        _ => {
+            println!("Part 1: {}", part1(input).unwrap());
+            println!("Part 2: {}", part2(input).unwrap());
+        }
+    }
 }
@@ -1,68 +1,597 @@
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet};
 
-use anyhow::{anyhow, bail, Ok, Result};
+use anyhow::{anyhow, bail, Result};
+use itertools::Itertools;
+use regex::Regex;
+use serde_json::{json, Map, Value};
 #[allow(unused_imports)]
 use tap::Tap;
 
-#[derive(Debug, Clone, Copy)]
-struct Node<'a>(&'a str, &'a str);
+#[derive(Debug, Clone)]
+struct Node<'a>(Vec<&'a NodeKey>);
 
 pub fn part1(input: &str) -> Result<usize> {
-    let (instructions, node_map) = parse_data1(input)?;
-
-    let mut key = "AAA";
-
-    for (step, instruction) in instructions.chars().cycle().enumerate() {
-        let node = node_map.get(key).ok_or(anyhow!("Node Not Found"))?;
-
-        key = match instruction {
-            'L' => node.0,
-            'R' => node.1,
-            _ => return Err(anyhow!("Invalid Instruction")),
-        };
-
-        if key == "ZZZ" {
-            return Ok(step + 1);
-        }
-    }
-
-    bail!("No Solution")
+    Network::parse(input)?.walk(
+        &Matcher::Exact("AAA".to_string()),
+        &Matcher::Exact("ZZZ".to_string()),
+    )
 }
 
+type Alphabet = str;
 type Instructions = str;
 type NodeKey = str;
-fn parse_data1(input: &str) -> Result<(&Instructions, HashMap<&NodeKey, Node<'_>>)> {
-    let (instructions, rules) = input
+
+/// Parse the instructions, with the optional `alphabet:` line declaring
+/// which character picks which edge, and every node rule.
+fn parse_data1(input: &str) -> Result<(&Alphabet, &Instructions, HashMap<&NodeKey, Node<'_>>)> {
+    let (header, rules) = input
         .split_once("\n\n")
         .ok_or(anyhow!("Can't Get Instructions"))?;
+    let (alphabet, instructions) = match header.trim().split_once('\n') {
+        Some((alphabet, instructions)) => (
+            alphabet
+                .trim()
+                .strip_prefix("alphabet:")
+                .ok_or(anyhow!("Can't Get Alphabet"))?
+                .trim(),
+            instructions.trim(),
+        ),
+        None => ("LR", header.trim()),
+    };
+
     let node_map = rules
         .lines()
         .map(|line| {
             let (name, rule) = line.split_once('=').ok_or(anyhow!("Can't Get Rule"))?;
 
             let node = rule
-                .split_once(',')
-                .map(|(left, right)| {
-                    Node(
-                        left.trim().trim_start_matches('('),
-                        right.trim().trim_end_matches(')'),
-                    )
-                })
+                .trim()
+                .strip_prefix('(')
+                .and_then(|rule| rule.strip_suffix(')'))
+                .map(|rule| Node(rule.split(',').map(str::trim).collect()))
                 .ok_or(anyhow!("Can't Get Node"))?;
 
             Ok((name.trim(), node))
         })
         .collect::<Result<HashMap<_, _>>>()?;
-    Ok((instructions, node_map))
+    Ok((alphabet, instructions, node_map))
+}
+
+/// Which node names a walk starts from or stops at.
+#[derive(Debug, Clone)]
+pub enum Matcher {
+    Exact(String),
+    Suffix(String),
+    Regex(Regex),
+}
+
+impl Matcher {
+    pub fn regex(pattern: &str) -> Result<Self> {
+        Ok(Matcher::Regex(Regex::new(pattern)?))
+    }
+
+    pub fn matches(&self, name: &NodeKey) -> bool {
+        match self {
+            Matcher::Exact(exact) => name == exact,
+            Matcher::Suffix(suffix) => name.ends_with(suffix.as_str()),
+            Matcher::Regex(regex) => regex.is_match(name),
+        }
+    }
+}
+
+/// The network with every node interned to a dense id, so a walk is plain
+/// indexing and names are only needed for reporting.
+#[derive(Debug)]
+pub struct Network<'a> {
+    /// Instruction characters in the order of the edges they select.
+    alphabet: Vec<char>,
+    /// Instructions as edge indexes into `alphabet`.
+    instructions: Vec<usize>,
+    names: Vec<&'a NodeKey>,
+    ids: HashMap<&'a NodeKey, usize>,
+    edges: Vec<Vec<usize>>,
+}
+
+impl<'a> Network<'a> {
+    pub fn parse(input: &'a str) -> Result<Self> {
+        let (alphabet, instructions, node_map) = parse_data1(input)?;
+        Self::compile(alphabet, instructions, &node_map)
+    }
+
+    fn compile(
+        alphabet: &Alphabet,
+        instructions: &Instructions,
+        node_map: &HashMap<&'a NodeKey, Node<'a>>,
+    ) -> Result<Self> {
+        let alphabet = alphabet.chars().collect_vec();
+        if alphabet.is_empty() {
+            bail!("Empty Alphabet");
+        }
+        if !alphabet.iter().all_unique() {
+            bail!("Duplicate Instruction");
+        }
+
+        let instructions = instructions
+            .chars()
+            .map(|instruction| {
+                alphabet
+                    .iter()
+                    .position(|c| *c == instruction)
+                    .ok_or(anyhow!("Invalid Instruction"))
+            })
+            .collect::<Result<Vec<_>>>()?;
+        if instructions.is_empty() {
+            bail!("Empty Instructions");
+        }
+
+        let names = node_map.keys().copied().sorted().collect_vec();
+        let ids: HashMap<_, _> = names
+            .iter()
+            .enumerate()
+            .map(|(id, name)| (*name, id))
+            .collect();
+        let edges = names
+            .iter()
+            .map(|name| {
+                let Node(targets) = &node_map[name];
+                if targets.len() != alphabet.len() {
+                    bail!("Wrong Edge Count");
+                }
+                targets
+                    .iter()
+                    .map(|target| ids.get(target).copied().ok_or(anyhow!("Node Not Found")))
+                    .collect()
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Network {
+            alphabet,
+            instructions,
+            names,
+            ids,
+            edges,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.names.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.names.is_empty()
+    }
+
+    pub fn alphabet(&self) -> &[char] {
+        &self.alphabet
+    }
+
+    pub fn id(&self, name: &NodeKey) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    pub fn name(&self, id: usize) -> &'a NodeKey {
+        self.names[id]
+    }
+
+    /// Ids of every node whose name matches `predicate`, in name order.
+    pub fn ids_where(&self, predicate: impl Fn(&NodeKey) -> bool) -> Vec<usize> {
+        (0..self.len())
+            .filter(|id| predicate(self.names[*id]))
+            .collect()
+    }
+
+    /// Node reached from `id` by the instruction taken on `step`.
+    pub fn next(&self, id: usize, step: usize) -> usize {
+        self.edges[id][self.instructions[step % self.instructions.len()]]
+    }
+
+    /// Steps from the single node matching `start` to the first node
+    /// matching `goal`, or an error once the walk repeats a node at the same
+    /// place in the instructions without having reached the goal.
+    pub fn walk(&self, start: &Matcher, goal: &Matcher) -> Result<usize> {
+        let id = match self.ids_where(|name| start.matches(name))[..] {
+            [id] => id,
+            [] => bail!("Node Not Found"),
+            _ => bail!("Several Start Nodes"),
+        };
+        let goals = self.goals(goal);
+        if goals.is_empty() {
+            bail!("No Solution");
+        }
+
+        let cycle = match trace(self, id, &goals, true) {
+            Trace::Goal(step) => return Ok(step),
+            Trace::Cycle(cycle) => cycle,
+        };
+        let entry = (0..cycle.offset).fold(id, |id, step| self.next(id, step));
+        bail!(
+            "No Solution: Loops From Step {} At {} Every {} Steps",
+            cycle.offset,
+            self.name(entry),
+            cycle.length
+        )
+    }
+
+    /// Steps until walks from every node matching `start` stand on nodes
+    /// matching `goal` at the same time.
+    pub fn walk_all(&self, start: &Matcher, goal: &Matcher) -> Result<usize> {
+        let goals = self.goals(goal);
+
+        let cycles = self
+            .ids_where(|name| start.matches(name))
+            .into_iter()
+            .map(|start| find_cycle(self, start, &goals))
+            .collect_vec();
+
+        earliest_common_hit(&cycles)?.ok_or(anyhow!("No Solution"))
+    }
+
+    fn goals(&self, goal: &Matcher) -> HashSet<usize> {
+        self.ids_where(|name| goal.matches(name))
+            .into_iter()
+            .collect()
+    }
+
+    /// The loop a walk from `start` settles into, with its hits on `goal`.
+    pub fn cycle(&self, start: usize, goal: &Matcher) -> Cycle {
+        find_cycle(self, start, &self.goals(goal))
+    }
+
+    /// Nodes visited on one lap of `cycle`, in walking order.
+    pub fn lap(&self, start: usize, cycle: &Cycle) -> Vec<usize> {
+        let mut id = (0..cycle.offset).fold(start, |id, step| self.next(id, step));
+
+        (cycle.offset..cycle.offset + cycle.length)
+            .map(|step| {
+                let here = id;
+                id = self.next(id, step);
+                here
+            })
+            .collect()
+    }
+
+    /// Nodes no node matching `start` can reach, whatever the instructions.
+    pub fn unreachable(&self, start: &Matcher) -> Vec<usize> {
+        let mut seen = vec![false; self.len()];
+        let mut queue = self.ids_where(|name| start.matches(name));
+        queue.iter().for_each(|id| seen[*id] = true);
+
+        while let Some(id) = queue.pop() {
+            for target in &self.edges[id] {
+                if !seen[*target] {
+                    seen[*target] = true;
+                    queue.push(*target);
+                }
+            }
+        }
+
+        (0..self.len()).filter(|id| !seen[*id]).collect()
+    }
+
+    /// Strongly connected components over every edge, in reverse
+    /// topological order, each sorted by id.
+    pub fn strongly_connected_components(&self) -> Vec<Vec<usize>> {
+        let mut index = vec![None; self.len()];
+        let mut low = vec![0; self.len()];
+        let mut on_stack = vec![false; self.len()];
+        let mut stack = vec![];
+        let mut components = vec![];
+        let mut next_index = 0;
+
+        // Tarjan's algorithm, with an explicit stack of (node, next edge)
+        // instead of recursion.
+        for root in 0..self.len() {
+            if index[root].is_some() {
+                continue;
+            }
+
+            let mut work = vec![(root, 0)];
+            while let Some((id, edge)) = work.pop() {
+                if edge == 0 {
+                    index[id] = Some(next_index);
+                    low[id] = next_index;
+                    next_index += 1;
+                    stack.push(id);
+                    on_stack[id] = true;
+                }
+
+                if let Some(target) = self.edges[id].get(edge).copied() {
+                    work.push((id, edge + 1));
+                    match index[target] {
+                        None => work.push((target, 0)),
+                        Some(target_index) if on_stack[target] => {
+                            low[id] = low[id].min(target_index)
+                        }
+                        Some(_) => {}
+                    }
+                    continue;
+                }
+
+                if index[id] == Some(low[id]) {
+                    let mut component = vec![];
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == id {
+                            break;
+                        }
+                    }
+                    component.sort_unstable();
+                    components.push(component);
+                }
+                if let Some((parent, _)) = work.last() {
+                    low[*parent] = low[*parent].min(low[id]);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Graphviz DOT, with one edge per instruction character.
+    pub fn to_dot(&self) -> String {
+        let edges = self
+            .edges
+            .iter()
+            .enumerate()
+            .flat_map(|(id, targets)| {
+                targets
+                    .iter()
+                    .zip(&self.alphabet)
+                    .map(move |(target, instruction)| {
+                        format!(
+                            "    {:?} -> {:?} [label={:?}];",
+                            self.names[id],
+                            self.names[*target],
+                            instruction.to_string()
+                        )
+                    })
+            })
+            .join("\n");
+
+        format!("digraph network {{\n{}\n}}\n", edges)
+    }
+
+    /// JSON with the alphabet, the instructions and each node's edges keyed
+    /// by instruction character.
+    pub fn to_json(&self) -> String {
+        let nodes = self
+            .names
+            .iter()
+            .zip(&self.edges)
+            .map(|(name, targets)| {
+                let edges = self
+                    .alphabet
+                    .iter()
+                    .zip(targets)
+                    .map(|(instruction, target)| {
+                        (instruction.to_string(), json!(self.names[*target]))
+                    })
+                    .collect::<Map<_, _>>();
+                (name.to_string(), Value::Object(edges))
+            })
+            .collect::<Map<_, _>>();
+
+        let value = json!({
+            "alphabet": self.alphabet.iter().collect::<String>(),
+            "instructions": self
+                .instructions
+                .iter()
+                .map(|instruction| self.alphabet[*instruction])
+                .collect::<String>(),
+            "nodes": nodes,
+        });
+        format!("{:#}", value)
+    }
 }
 
-#[allow(unused_variables)]
 pub fn part2(input: &str) -> Result<usize> {
-    Ok(6)
+    Network::parse(input)?.walk_all(
+        &Matcher::Suffix("A".to_string()),
+        &Matcher::Suffix("Z".to_string()),
+    )
+}
+
+/// Components, unreachable nodes and the loop each start settles into.
+pub fn analysis_report(network: &Network, start: &Matcher, goal: &Matcher) -> String {
+    let components = network.strongly_connected_components();
+    let unreachable = network.unreachable(start);
+
+    let mut lines = vec![
+        format!("Nodes: {}", network.len()),
+        format!(
+            "Strongly connected components: {} (largest {})",
+            components.len(),
+            components.iter().map(Vec::len).max().unwrap_or(0)
+        ),
+        format!(
+            "Unreachable from start: {} [{}]",
+            unreachable.len(),
+            unreachable.iter().map(|id| network.name(*id)).join(", ")
+        ),
+    ];
+
+    for id in network.ids_where(|name| start.matches(name)) {
+        let cycle = network.cycle(id, goal);
+        let lap = network.lap(id, &cycle);
+        lines.push(format!(
+            "{}: loops after {} steps every {} steps through {} nodes, goal at {:?}",
+            network.name(id),
+            cycle.offset,
+            cycle.length,
+            lap.iter().unique().count(),
+            cycle.hits
+        ));
+    }
+
+    lines.join("\n")
+}
+
+/// The walk of one ghost: after `offset` steps it repeats every `length`
+/// steps, since both its node and its place in the instructions repeat.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Cycle {
+    pub offset: usize,
+    pub length: usize,
+    /// Steps before `offset` that end on a goal node.
+    pub prefix_hits: Vec<usize>,
+    /// Steps from `offset` on, modulo `length`, that end on a goal node.
+    pub hits: Vec<usize>,
+}
+
+impl Cycle {
+    fn is_hit(&self, step: usize) -> bool {
+        if step < self.offset {
+            self.prefix_hits.contains(&step)
+        } else {
+            self.hits.contains(&((step - self.offset) % self.length))
+        }
+    }
+}
+
+fn find_cycle(network: &Network, start: usize, goals: &HashSet<usize>) -> Cycle {
+    match trace(network, start, goals, false) {
+        Trace::Cycle(cycle) => cycle,
+        Trace::Goal(_) => unreachable!(),
+    }
+}
+
+/// How a walk from a single node ended.
+enum Trace {
+    /// Stood on a goal after this many steps.
+    Goal(usize),
+    /// Repeated a node at the same place in the instructions.
+    Cycle(Cycle),
+}
+
+/// Walk from `start` until a (node, instruction) state repeats. With
+/// `stop_at_goal` the walk ends early on the first goal after step 0.
+fn trace(network: &Network, start: usize, goals: &HashSet<usize>, stop_at_goal: bool) -> Trace {
+    let period = network.instructions.len();
+    let mut seen = HashMap::new();
+    let mut hits = vec![];
+    let mut id = start;
+
+    for step in 0.. {
+        if stop_at_goal && step > 0 && goals.contains(&id) {
+            return Trace::Goal(step);
+        }
+        if let Some(offset) = seen.insert((id, step % period), step) {
+            let (prefix_hits, hits) = hits.into_iter().partition(|hit| *hit < offset);
+
+            return Trace::Cycle(Cycle {
+                offset,
+                length: step - offset,
+                prefix_hits,
+                hits: hits.into_iter().map(|hit| hit - offset).collect(),
+            });
+        }
+        if goals.contains(&id) {
+            hits.push(step);
+        }
+
+        id = network.next(id, step);
+    }
+
+    unreachable!()
+}
+
+/// First step on which every ghost stands on a `..Z` node at once.
+fn earliest_common_hit(cycles: &[Cycle]) -> Result<Option<usize>> {
+    let Some(offset) = cycles.iter().map(|cycle| cycle.offset).max() else {
+        return Ok(None);
+    };
+
+    // Before every ghost is inside its loop the hits have to be checked one
+    // by one; afterwards each ghost only allows a few residues.
+    let early = (1..offset).find(|step| cycles.iter().all(|cycle| cycle.is_hit(*step)));
+    if early.is_some() {
+        return Ok(early);
+    }
+
+    // Merge the ghosts one at a time, keeping every residue still possible
+    // modulo the combined period.
+    let mut residues = vec![0];
+    let mut modulus = 1;
+    for cycle in cycles {
+        let length = cycle.length as i128;
+        let mut merged = vec![];
+        for residue in &residues {
+            for hit in &cycle.hits {
+                let congruence = ((cycle.offset + hit) as i128, length);
+                if let Some((merged_residue, merged_modulus)) =
+                    crt((*residue, modulus), congruence)?
+                {
+                    merged.push((merged_residue, merged_modulus));
+                }
+            }
+        }
+
+        let Some((_, merged_modulus)) = merged.first().copied() else {
+            return Ok(None);
+        };
+        residues = merged
+            .into_iter()
+            .map(|(residue, _)| residue)
+            .sorted()
+            .dedup()
+            .collect();
+        modulus = merged_modulus;
+    }
+
+    let offset = offset.max(1) as i128;
+    let step = residues
+        .into_iter()
+        .map(|residue| {
+            let gap = (offset - residue).max(0);
+            ((gap + modulus - 1) / modulus)
+                .checked_mul(modulus)
+                .and_then(|lift| lift.checked_add(residue))
+                .ok_or_else(|| anyhow!("Overflow"))
+        })
+        .process_results(|steps| steps.min())?;
+
+    step.map(|step| usize::try_from(step).map_err(|_| anyhow!("Overflow")))
+        .transpose()
+}
+
+/// Merge `x ≡ a (mod m)` and `x ≡ b (mod n)` for moduli that need not be
+/// coprime, or `None` when they contradict each other.
+fn crt((a, m): (i128, i128), (b, n): (i128, i128)) -> Result<Option<(i128, i128)>> {
+    let overflow = || anyhow!("Overflow");
+    let (g, p, _) = extended_gcd(m, n);
+    if (b - a) % g != 0 {
+        return Ok(None);
+    }
+
+    let lcm = (m / g).checked_mul(n).ok_or_else(overflow)?;
+    let step = n / g;
+    let k = ((b - a) / g)
+        .rem_euclid(step)
+        .checked_mul(p.rem_euclid(step))
+        .ok_or_else(overflow)?
+        .rem_euclid(step);
+    let x = m
+        .checked_mul(k)
+        .and_then(|mk| a.checked_add(mk))
+        .ok_or_else(overflow)?;
+
+    Ok(Some((x.rem_euclid(lcm), lcm)))
+}
+
+fn extended_gcd(a: i128, b: i128) -> (i128, i128, i128) {
+    if b == 0 {
+        (a, 1, 0)
+    } else {
+        let (g, x, y) = extended_gcd(b, a % b);
+        (g, y, x - a / b * y)
+    }
 }
 
 #[cfg(test)]
 mod tests {
+    use proptest::prelude::*;
+
     use super::*;
 
     #[test]
@@ -79,4 +608,324 @@ mod tests {
         let input = include_str!("../example3");
         assert_eq!(part2(input).unwrap(), 6);
     }
+
+    #[test]
+    fn test_find_cycle() {
+        let input = include_str!("../example3");
+        let network = Network::parse(input).unwrap();
+        let goals = network
+            .ids_where(|name| name.ends_with('Z'))
+            .into_iter()
+            .collect();
+
+        assert_eq!(
+            find_cycle(&network, network.id("11A").unwrap(), &goals),
+            Cycle {
+                offset: 1,
+                length: 2,
+                prefix_hits: vec![],
+                hits: vec![1],
+            }
+        );
+        assert_eq!(
+            find_cycle(&network, network.id("22A").unwrap(), &goals),
+            Cycle {
+                offset: 1,
+                length: 6,
+                prefix_hits: vec![],
+                hits: vec![2, 5],
+            }
+        );
+    }
+
+    #[test]
+    fn test_network() {
+        let input = include_str!("../example2");
+        let network = Network::parse(input).unwrap();
+
+        assert_eq!(network.len(), 3);
+        assert_eq!(network.name(network.id("BBB").unwrap()), "BBB");
+        assert_eq!(network.id("CCC"), None);
+
+        let aaa = network.id("AAA").unwrap();
+        let bbb = network.id("BBB").unwrap();
+        assert_eq!(network.next(aaa, 0), bbb);
+        assert_eq!(network.next(bbb, 2), network.id("ZZZ").unwrap());
+
+        assert!(Network::parse("LR\n\nAAA = (BBB, AAA)").is_err());
+        assert!(Network::parse("LX\n\nAAA = (AAA, AAA)").is_err());
+    }
+
+    #[test]
+    fn test_walk_detects_loop() {
+        let input = include_str!("../example1");
+        let network = Network::parse(input).unwrap();
+        let error = network
+            .walk(
+                &Matcher::Exact("DDD".to_string()),
+                &Matcher::Exact("ZZZ".to_string()),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No Solution: Loops From Step 0 At DDD Every 2 Steps"
+        );
+
+        let input = include_str!("../example3");
+        let network = Network::parse(input).unwrap();
+        let error = network
+            .walk(
+                &Matcher::Exact("11A".to_string()),
+                &Matcher::Exact("22Z".to_string()),
+            )
+            .unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "No Solution: Loops From Step 1 At 11B Every 2 Steps"
+        );
+
+        // Starting on a goal still takes at least one step.
+        let zzz = Matcher::Exact("ZZZ".to_string());
+        let network = Network::parse(include_str!("../example2")).unwrap();
+        assert_eq!(network.walk(&zzz, &zzz).unwrap(), 1);
+
+        // Even when that first step already repeats the starting state.
+        let network = Network::parse("L\n\nZZZ = (ZZZ, ZZZ)").unwrap();
+        assert_eq!(network.walk(&zzz, &zzz).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_matcher() {
+        assert!(Matcher::Exact("AAA".to_string()).matches("AAA"));
+        assert!(!Matcher::Exact("AAA".to_string()).matches("BAAA"));
+        assert!(Matcher::Suffix("Z".to_string()).matches("11Z"));
+        assert!(!Matcher::Suffix("Z".to_string()).matches("Z11"));
+        assert!(Matcher::regex("^1+Z$").unwrap().matches("11Z"));
+        assert!(!Matcher::regex("^1+Z$").unwrap().matches("12Z"));
+        assert!(Matcher::regex("(").is_err());
+    }
+
+    #[test]
+    fn test_alphabet() {
+        let input = "alphabet: <^>
+^<
+
+AAA = (AAA, BBB, CCC)
+BBB = (ZZZ, AAA, CCC)
+CCC = (AAA, AAA, BBB)
+ZZZ = (ZZZ, ZZZ, ZZZ)";
+        let network = Network::parse(input).unwrap();
+        assert_eq!(network.alphabet(), ['<', '^', '>']);
+
+        let start = Matcher::Exact("AAA".to_string());
+        assert_eq!(
+            network
+                .walk(&start, &Matcher::Exact("ZZZ".to_string()))
+                .unwrap(),
+            2
+        );
+        assert_eq!(
+            network
+                .walk(&start, &Matcher::regex("^[BC]").unwrap())
+                .unwrap(),
+            1
+        );
+        assert!(network
+            .walk(&Matcher::Suffix("C".to_string()), &start)
+            .is_ok());
+        assert!(network
+            .walk(&Matcher::regex("^[AB]").unwrap(), &start)
+            .is_err());
+
+        assert!(Network::parse("alphabet: LL\nL\n\nAAA = (AAA, AAA)").is_err());
+        assert!(Network::parse("alphabet: LRU\nL\n\nAAA = (AAA, AAA)").is_err());
+        assert!(Network::parse("L\nR\n\nAAA = (AAA, AAA)").is_err());
+    }
+
+    #[test]
+    fn test_export() {
+        let input = include_str!("../example2");
+        let network = Network::parse(input).unwrap();
+
+        let dot = network.to_dot();
+        assert!(dot.starts_with("digraph network {\n"));
+        assert!(dot.contains("    \"BBB\" -> \"ZZZ\" [label=\"R\"];\n"));
+        assert_eq!(dot.lines().count(), 2 + 3 * 2);
+
+        let json: Value = serde_json::from_str(&network.to_json()).unwrap();
+        assert_eq!(json["alphabet"], "LR");
+        assert_eq!(json["instructions"], "LLR");
+        assert_eq!(json["nodes"]["BBB"], json!({ "L": "AAA", "R": "ZZZ" }));
+    }
+
+    #[test]
+    fn test_analysis() {
+        let input = include_str!("../example3");
+        let network = Network::parse(input).unwrap();
+        let names = |ids: &[usize]| ids.iter().map(|id| network.name(*id)).collect_vec();
+
+        let components = network
+            .strongly_connected_components()
+            .iter()
+            .map(|component| names(component))
+            .sorted()
+            .collect_vec();
+        assert_eq!(
+            components,
+            vec![
+                vec!["11A"],
+                vec!["11B", "11Z"],
+                vec!["22A"],
+                vec!["22B", "22C", "22Z"],
+                vec!["XXX"],
+            ]
+        );
+
+        let start = Matcher::Suffix("A".to_string());
+        assert!(network.unreachable(&start).is_empty());
+        assert_eq!(
+            names(&network.unreachable(&Matcher::Exact("22B".to_string()))),
+            ["11A", "11B", "11Z", "22A", "XXX"]
+        );
+
+        let ghost = network.id("22A").unwrap();
+        let cycle = network.cycle(ghost, &Matcher::Suffix("Z".to_string()));
+        assert_eq!(
+            names(&network.lap(ghost, &cycle)),
+            ["22B", "22C", "22Z", "22B", "22C", "22Z"]
+        );
+
+        let report = analysis_report(&network, &start, &Matcher::Suffix("Z".to_string()));
+        assert!(report.contains("Strongly connected components: 5 (largest 3)"));
+        assert!(report.contains("22A: loops after 1 steps every 6 steps through 3 nodes"));
+    }
+
+    #[test]
+    fn test_earliest_common_hit() {
+        let cycle = |length, hits: Vec<usize>| Cycle {
+            offset: 0,
+            length,
+            prefix_hits: vec![],
+            hits,
+        };
+
+        // Many ghosts with many hits each would be far too many combinations
+        // to try one by one.
+        let cycles = (0..8)
+            .map(|_| cycle(60, (0..60).step_by(2).collect()))
+            .collect_vec();
+        assert_eq!(earliest_common_hit(&cycles).unwrap(), Some(2));
+
+        let cycles = [cycle(4, vec![1]), cycle(6, vec![3]), cycle(5, vec![2])];
+        assert_eq!(earliest_common_hit(&cycles).unwrap(), Some(57));
+
+        let cycles = [cycle(4, vec![1]), cycle(6, vec![2])];
+        assert_eq!(earliest_common_hit(&cycles).unwrap(), None);
+
+        let large = 1_000_000_000_000_000_003;
+        let cycles = [
+            cycle(large, vec![1]),
+            cycle(large + 2, vec![1]),
+            cycle(large + 4, vec![1]),
+        ];
+        assert!(earliest_common_hit(&cycles).is_err());
+    }
+
+    #[test]
+    fn test_part2_offsets_and_several_hits() {
+        // 11A reaches its loop late and hits Z twice per lap, 22A only
+        // hits Z before falling into a loop without one.
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11C, 11C)
+11C = (11Z, 11Z)
+11Z = (11D, 11D)
+11D = (11Y, 11Y)
+11Y = (11Z, 11Z)
+22A = (22Z, 22Z)
+22Z = (22B, 22B)
+22B = (22B, 22B)";
+        assert!(part2(input).is_err());
+
+        let input = "L
+
+11A = (11B, 11B)
+11B = (11Z, 11Z)
+11Z = (11C, 11C)
+11C = (11Z, 11Z)
+22A = (22B, 22B)
+22B = (22C, 22C)
+22C = (22Z, 22Z)
+22Z = (22B, 22B)";
+        assert_eq!(part2(input).unwrap(), brute_force(input, 1000).unwrap());
+    }
+
+    fn brute_force(input: &str, limit: usize) -> Option<usize> {
+        let (_, instructions, node_map) = parse_data1(input).unwrap();
+        let mut keys = node_map
+            .keys()
+            .filter(|key| key.ends_with('A'))
+            .copied()
+            .collect_vec();
+        if keys.is_empty() {
+            return None;
+        }
+
+        for (step, instruction) in instructions.chars().cycle().enumerate().take(limit) {
+            keys = keys
+                .iter()
+                .map(|key| {
+                    let Node(targets) = &node_map[key];
+                    if instruction == 'L' {
+                        targets[0]
+                    } else {
+                        targets[1]
+                    }
+                })
+                .collect();
+            if keys.iter().all(|key| key.ends_with('Z')) {
+                return Some(step + 1);
+            }
+        }
+
+        None
+    }
+
+    prop_compose! {
+        fn network()(
+            size in 2..9usize,
+            instructions in "[LR]{1,4}",
+        )(
+            ends in prop::collection::vec(0..3usize, size),
+            edges in prop::collection::vec((0..size, 0..size), size),
+            instructions in Just(instructions),
+        ) -> String {
+            let names = ends
+                .iter()
+                .enumerate()
+                .map(|(i, end)| format!("{}{}", i + 10, ['A', 'Z', 'X'][*end]))
+                .collect_vec();
+            let rules = edges
+                .iter()
+                .enumerate()
+                .map(|(i, (left, right))| {
+                    format!("{} = ({}, {})", names[i], names[*left], names[*right])
+                })
+                .join("\n");
+
+            format!("{}\n\n{}", instructions, rules)
+        }
+    }
+
+    proptest! {
+        #[test]
+        fn prop_part2_matches_brute_force(input in network()) {
+            let limit = 5000;
+            match brute_force(&input, limit) {
+                Some(step) => prop_assert_eq!(part2(&input).unwrap(), step),
+                None => prop_assert!(part2(&input).map_or(true, |step| step > limit)),
+            }
+        }
+    }
 }
@@ -1,5 +1,22 @@
+use std::env;
+
+use day08::{analysis_report, part1, part2, Matcher, Network};
+
 fn main() {
     let input = include_str!("../input");
-    println!("Part 1: {}", day08::part1(input).unwrap());
-    println!("Part 2: {}", day08::part2(input).unwrap());
+
+    match env::args().nth(1).as_deref() {
+        Some("dot") => print!("{}", Network::parse(input).unwrap().to_dot()),
+        Some("json") => println!("{}", Network::parse(input).unwrap().to_json()),
+        Some("analyze") => {
+            let network = Network::parse(input).unwrap();
+            let start = Matcher::Suffix("A".to_string());
+            let goal = Matcher::Suffix("Z".to_string());
+            println!("{}", analysis_report(&network, &start, &goal));
+        }
+        _ => {
+            println!("Part 1: {}", part1(input).unwrap());
+            println!("Part 2: {}", part2(input).unwrap());
+        }
+    }
 }
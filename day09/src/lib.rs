@@ -1,18 +1,29 @@
-use anyhow::{Context, Result};
+use anyhow::{anyhow, bail, Context, Result};
 use itertools::Itertools;
+use num_bigint::BigInt;
+use num_integer::Integer;
+use num_rational::BigRational;
+use num_traits::{One, Zero};
 
 pub fn part1(input: &str) -> Result<isize> {
-    input
-        .lines()
-        .map(|line| {
-            line.split_ascii_whitespace()
-                .map(|s| s.parse().context("Failed to parse input"))
-                .collect::<Result<Vec<_>>>()
+    parse_sequences(input)?
+        .iter()
+        .map(|layer| get_next_number(layer))
+        .try_fold(0isize, |sum, number| {
+            sum.checked_add(number?).ok_or_else(|| anyhow!("Overflow"))
         })
-        .process_results(|iter| iter.map(|layer| get_next_number(layer.as_slice())).sum())
 }
 
 pub fn part2(input: &str) -> Result<isize> {
+    parse_sequences(input)?
+        .iter()
+        .map(|layer| get_previous_number(layer))
+        .try_fold(0isize, |sum, number| {
+            sum.checked_add(number?).ok_or_else(|| anyhow!("Overflow"))
+        })
+}
+
+fn parse_sequences(input: &str) -> Result<Vec<Vec<isize>>> {
     input
         .lines()
         .map(|line| {
@@ -20,41 +31,293 @@ pub fn part2(input: &str) -> Result<isize> {
                 .map(|s| s.parse().context("Failed to parse input"))
                 .collect::<Result<Vec<_>>>()
         })
-        .process_results(|iter| {
-            iter.map(|layer| get_previous_number(layer.as_slice()))
-                .sum()
-        })
+        .collect()
 }
 
-fn get_sub_layer(layer: &[isize]) -> Option<Vec<isize>> {
+/// Differences of `layer`, or `None` once the layer is all zeros.
+fn get_sub_layer(layer: &[isize]) -> Result<Option<Vec<isize>>> {
     match layer {
-        [.., 0] => None,
-        _ => Some(
-            layer
-                .iter()
-                .tuple_windows()
-                .map(|(a, b)| b - a)
-                .collect_vec(),
-        ),
+        [] => bail!("Empty Sequence"),
+        _ if layer.iter().all(|value| *value == 0) => Ok(None),
+        [_] => bail!("Never Reaches Zero"),
+        _ => layer
+            .iter()
+            .tuple_windows()
+            .map(|(a, b)| b.checked_sub(*a).ok_or_else(|| anyhow!("Overflow")))
+            .collect::<Result<Vec<_>>>()
+            .map(Some),
+    }
+}
+
+fn get_next_number(layer: &[isize]) -> Result<isize> {
+    let last = *layer.last().ok_or_else(|| anyhow!("Empty Sequence"))?;
+    match get_sub_layer(layer)? {
+        Some(sub_layer) => last
+            .checked_add(get_next_number(sub_layer.as_slice())?)
+            .ok_or_else(|| anyhow!("Overflow")),
+        None => Ok(last),
     }
 }
 
-fn get_next_number(layer: &[isize]) -> isize {
-    let last = layer[layer.len() - 1];
-    match get_sub_layer(layer) {
-        Some(sub_layer) => last + get_next_number(sub_layer.as_slice()),
-        None => last,
+fn get_previous_number(layer: &[isize]) -> Result<isize> {
+    let first = *layer.first().ok_or_else(|| anyhow!("Empty Sequence"))?;
+    match get_sub_layer(layer)? {
+        Some(sub_layer) => first
+            .checked_sub(get_previous_number(sub_layer.as_slice())?)
+            .ok_or_else(|| anyhow!("Overflow")),
+        None => Ok(first),
+    }
+}
+
+/// Sum of every sequence extended `steps` past its last value, or before
+/// its first one when `steps` is negative.
+pub fn extrapolate(input: &str, steps: isize) -> Result<i128> {
+    parse_sequences(input)?
+        .iter()
+        .map(|sequence| {
+            let newton = Newton::fit(sequence)?;
+            if steps < 0 {
+                newton.previous(steps.unsigned_abs())
+            } else {
+                newton.next(steps.unsigned_abs())
+            }
+        })
+        .try_fold(0i128, |sum, value| {
+            sum.checked_add(value?).ok_or_else(|| anyhow!("Overflow"))
+        })
+}
+
+/// The polynomial through a sequence, kept as Newton backward differences
+/// at its last value so any position is one sum of binomial terms.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Newton {
+    len: usize,
+    /// `diagonal[j]` is the `j`-th backward difference at the last value,
+    /// without trailing zeros.
+    diagonal: Vec<i128>,
+}
+
+impl Newton {
+    /// Builds the differences in one pass over the sequence, keeping only
+    /// the latest diagonal of the difference table. Like the layer method it
+    /// needs the sequence to reach an all-zero layer.
+    pub fn fit(sequence: &[isize]) -> Result<Newton> {
+        if sequence.is_empty() {
+            bail!("Empty Sequence");
+        }
+
+        let mut diagonal: Vec<i128> = Vec::with_capacity(sequence.len());
+        for value in sequence {
+            let mut difference = *value as i128;
+            for previous in diagonal.iter_mut() {
+                let next = difference
+                    .checked_sub(*previous)
+                    .ok_or_else(|| anyhow!("Overflow"))?;
+                (*previous, difference) = (difference, next);
+            }
+            diagonal.push(difference);
+        }
+        while diagonal.last() == Some(&0) {
+            diagonal.pop();
+        }
+        if diagonal.len() == sequence.len() {
+            bail!("Never Reaches Zero");
+        }
+
+        Ok(Newton {
+            len: sequence.len(),
+            diagonal,
+        })
+    }
+
+    /// Degree of the fitted polynomial, `None` when it is zero everywhere.
+    pub fn degree(&self) -> Option<usize> {
+        self.diagonal.len().checked_sub(1)
+    }
+
+    /// Value at `position`, where the sequence covers `0..len`.
+    pub fn value_at(&self, position: i128) -> Result<i128> {
+        let overflow = || anyhow!("Overflow");
+        let t = position
+            .checked_sub(self.len as i128 - 1)
+            .ok_or_else(overflow)?;
+
+        // C(t + j - 1, j) follows from the previous term times (t + j - 1) / j.
+        let mut binomial = 1i128;
+        let mut value = 0i128;
+        for (j, difference) in self.diagonal.iter().enumerate() {
+            if j > 0 {
+                let factor = t.checked_add(j as i128 - 1).ok_or_else(overflow)?;
+                binomial = binomial.checked_mul(factor).ok_or_else(overflow)? / j as i128;
+            }
+            value = difference
+                .checked_mul(binomial)
+                .and_then(|term| value.checked_add(term))
+                .ok_or_else(overflow)?;
+        }
+
+        Ok(value)
+    }
+
+    /// Value `steps` after the last one of the sequence.
+    pub fn next(&self, steps: usize) -> Result<i128> {
+        self.value_at(self.len as i128 - 1 + steps as i128)
     }
+
+    /// Value `steps` before the first one of the sequence.
+    pub fn previous(&self, steps: usize) -> Result<i128> {
+        self.value_at(-(steps as i128))
+    }
+}
+
+/// The polynomial through a set of points, found by Lagrange interpolation
+/// over exact rationals so it can be evaluated anywhere, fractions included.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Lagrange {
+    /// Monomial coefficients, constant term first, without trailing zeros.
+    coefficients: Vec<BigRational>,
 }
 
-fn get_previous_number(layer: &[isize]) -> isize {
-    let first = layer[0];
-    match get_sub_layer(layer) {
-        Some(sub_layer) => first - get_previous_number(sub_layer.as_slice()),
-        None => first,
+impl Lagrange {
+    /// Interpolates a sequence sampled at positions `0..len`.
+    pub fn from_sequence(sequence: &[isize]) -> Result<Lagrange> {
+        let points = sequence
+            .iter()
+            .enumerate()
+            .map(|(x, y)| {
+                (
+                    BigRational::from_integer(x.into()),
+                    BigRational::from_integer((*y).into()),
+                )
+            })
+            .collect_vec();
+        Lagrange::fit(&points)
+    }
+
+    pub fn fit(points: &[(BigRational, BigRational)]) -> Result<Lagrange> {
+        if points.is_empty() {
+            bail!("Empty Sequence");
+        }
+        if !points.iter().map(|(x, _)| x).all_unique() {
+            bail!("Duplicate Position");
+        }
+
+        // Scale positions and values to integers, so the whole sum runs on
+        // big integers and is only reduced once per coefficient.
+        let x_scale = common_denominator(points.iter().map(|(x, _)| x));
+        let y_scale = common_denominator(points.iter().map(|(_, y)| y));
+        let xs = points
+            .iter()
+            .map(|(x, _)| (x * &x_scale).to_integer())
+            .collect_vec();
+        let ys = points
+            .iter()
+            .map(|(_, y)| (y * &y_scale).to_integer())
+            .collect_vec();
+
+        // Π (z - zⱼ), so each basis numerator is this with one factor divided
+        // back out.
+        let mut product = vec![BigInt::one()];
+        for xj in &xs {
+            product.insert(0, BigInt::zero());
+            for k in 0..product.len() - 1 {
+                let shifted = &product[k + 1] * xj;
+                product[k] -= shifted;
+            }
+        }
+
+        let weights = xs
+            .iter()
+            .map(|xi| {
+                xs.iter()
+                    .filter(|xj| *xj != xi)
+                    .fold(BigInt::one(), |weight, xj| weight * (xi - xj))
+            })
+            .collect_vec();
+        let denominator = weights
+            .iter()
+            .fold(BigInt::one(), |lcm, weight| lcm.lcm(weight));
+        let scales = ys
+            .iter()
+            .zip(&weights)
+            .map(|(yi, weight)| yi * (&denominator / weight))
+            .collect_vec();
+
+        // Dividing out (z - zᵢ) leaves Σₘ product[m] zᵢ^(m-k-1) at degree k,
+        // so the weighted sum only needs the power sums Σᵢ scaleᵢ zᵢ^t.
+        let mut powers = vec![BigInt::one(); xs.len()];
+        let power_sums = (0..xs.len())
+            .map(|_| {
+                let sum = scales
+                    .iter()
+                    .zip(&powers)
+                    .map(|(scale, power)| scale * power)
+                    .sum::<BigInt>();
+                powers
+                    .iter_mut()
+                    .zip(&xs)
+                    .for_each(|(power, xi)| *power *= xi);
+                sum
+            })
+            .collect_vec();
+
+        let mut x_power = BigInt::one();
+        let mut coefficients = (0..xs.len())
+            .map(|k| {
+                let numerator = (k + 1..=xs.len())
+                    .map(|m| &product[m] * &power_sums[m - k - 1])
+                    .sum::<BigInt>();
+                let coefficient = BigRational::new(numerator * &x_power, &denominator * &y_scale);
+                x_power *= &x_scale;
+                coefficient
+            })
+            .collect_vec();
+        while coefficients.last().is_some_and(Zero::is_zero) {
+            coefficients.pop();
+        }
+
+        Ok(Lagrange { coefficients })
+    }
+
+    pub fn coefficients(&self) -> &[BigRational] {
+        &self.coefficients
+    }
+
+    /// Degree of the polynomial, `None` when it is zero everywhere.
+    pub fn degree(&self) -> Option<usize> {
+        self.coefficients.len().checked_sub(1)
+    }
+
+    pub fn value_at(&self, x: &BigRational) -> BigRational {
+        self.coefficients
+            .iter()
+            .rev()
+            .fold(BigRational::zero(), |value, coefficient| {
+                value * x + coefficient
+            })
     }
 }
 
+fn common_denominator<'a>(values: impl Iterator<Item = &'a BigRational>) -> BigInt {
+    values.fold(BigInt::one(), |lcm, value| lcm.lcm(value.denom()))
+}
+
+/// Coefficients and value at `position` of the polynomial through each
+/// sequence.
+pub fn interpolate(
+    input: &str,
+    position: &BigRational,
+) -> Result<Vec<(Vec<BigRational>, BigRational)>> {
+    parse_sequences(input)?
+        .iter()
+        .map(|sequence| {
+            let lagrange = Lagrange::from_sequence(sequence)?;
+            let value = lagrange.value_at(position);
+            Ok((lagrange.coefficients, value))
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -68,25 +331,167 @@ mod tests {
     #[test]
     fn test_get_sub_layer() {
         assert_eq!(
-            get_sub_layer(&[1, 3, 6, 10, 15, 21]).unwrap(),
+            get_sub_layer(&[1, 3, 6, 10, 15, 21]).unwrap().unwrap(),
             [2, 3, 4, 5, 6]
         );
-        assert_eq!(get_sub_layer(&[2, 3, 4, 5, 6]).unwrap(), [1, 1, 1, 1]);
-        assert_eq!(get_sub_layer(&[1, 1, 1, 1]).unwrap(), [0, 0, 0,]);
-        assert_eq!(get_sub_layer(&[0, 0, 0,]), None);
+        assert_eq!(
+            get_sub_layer(&[2, 3, 4, 5, 6]).unwrap().unwrap(),
+            [1, 1, 1, 1]
+        );
+        assert_eq!(get_sub_layer(&[1, 1, 1, 1]).unwrap().unwrap(), [0, 0, 0,]);
+        assert_eq!(get_sub_layer(&[0, 0, 0,]).unwrap(), None);
+        assert_eq!(get_sub_layer(&[3, 0]).unwrap().unwrap(), [-3]);
+        assert!(get_sub_layer(&[-3]).is_err());
+        assert!(get_sub_layer(&[]).is_err());
+        assert!(get_sub_layer(&[isize::MIN, isize::MAX]).is_err());
     }
 
     #[test]
     fn test_get_next_number() {
-        assert_eq!(get_next_number(&[0, 3, 6, 9, 12, 15]), 18);
-        assert_eq!(get_next_number(&[1, 3, 6, 10, 15, 21]), 28);
-        assert_eq!(get_next_number(&[10, 13, 16, 21, 30, 45]), 68);
+        assert_eq!(get_next_number(&[0, 3, 6, 9, 12, 15]).unwrap(), 18);
+        assert_eq!(get_next_number(&[1, 3, 6, 10, 15, 21]).unwrap(), 28);
+        assert_eq!(get_next_number(&[10, 13, 16, 21, 30, 45]).unwrap(), 68);
+        assert_eq!(
+            get_next_number(&[0, 0, 5, 0, 0]).unwrap_err().to_string(),
+            "Never Reaches Zero"
+        );
+        assert_eq!(
+            get_next_number(&[]).unwrap_err().to_string(),
+            "Empty Sequence"
+        );
+        assert_eq!(
+            get_next_number(&[isize::MAX, isize::MAX]).unwrap(),
+            isize::MAX
+        );
+        assert!(get_next_number(&[isize::MAX - 1, isize::MAX]).is_err());
     }
 
     #[test]
     fn test_get_previous_number() {
-        assert_eq!(get_previous_number(&[0, 3, 6, 9, 12, 15]), -3);
-        assert_eq!(get_previous_number(&[1, 3, 6, 10, 15, 21]), 0);
-        assert_eq!(get_previous_number(&[10, 13, 16, 21, 30, 45]), 5);
+        assert_eq!(get_previous_number(&[0, 3, 6, 9, 12, 15]).unwrap(), -3);
+        assert_eq!(get_previous_number(&[1, 3, 6, 10, 15, 21]).unwrap(), 0);
+        assert_eq!(get_previous_number(&[10, 13, 16, 21, 30, 45]).unwrap(), 5);
+        assert!(get_previous_number(&[isize::MIN + 1, isize::MIN + 2]).is_err());
+    }
+
+    #[test]
+    fn test_newton() {
+        let newton = Newton::fit(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(newton.degree(), Some(3));
+        assert_eq!(newton.next(1).unwrap(), 68);
+        assert_eq!(newton.previous(1).unwrap(), 5);
+        assert_eq!(
+            (0..6).map(|i| newton.value_at(i).unwrap()).collect_vec(),
+            [10, 13, 16, 21, 30, 45]
+        );
+
+        // n² + n + 1 sampled at 0..4.
+        let newton = Newton::fit(&[1, 3, 7, 13]).unwrap();
+        assert_eq!(newton.degree(), Some(2));
+        assert_eq!(newton.next(3).unwrap(), 43);
+        assert_eq!(newton.previous(4).unwrap(), 13);
+
+        assert_eq!(Newton::fit(&[7, 7, 7]).unwrap().degree(), Some(0));
+        assert_eq!(Newton::fit(&[0, 0]).unwrap().degree(), None);
+        assert_eq!(Newton::fit(&[0, 0]).unwrap().next(5).unwrap(), 0);
+        assert!(Newton::fit(&[]).is_err());
+        assert_eq!(
+            Newton::fit(&[3, 0]).unwrap_err().to_string(),
+            "Never Reaches Zero"
+        );
+        assert_eq!(
+            Newton::fit(&[5]).unwrap_err().to_string(),
+            "Never Reaches Zero"
+        );
+        assert!(extrapolate("1 2", 1).is_err());
+        assert!(Newton::fit(&[0, 1, 4, 9])
+            .unwrap()
+            .next(usize::MAX)
+            .is_err());
+    }
+
+    #[test]
+    fn test_extrapolate() {
+        let input = include_str!("../example");
+        assert_eq!(extrapolate(input, 1).unwrap(), 114);
+        assert_eq!(extrapolate(input, -1).unwrap(), 2);
+
+        let input = include_str!("../input");
+        assert_eq!(
+            extrapolate(input, 1).unwrap(),
+            part1(input).unwrap() as i128
+        );
+        assert_eq!(
+            extrapolate(input, -1).unwrap(),
+            part2(input).unwrap() as i128
+        );
+    }
+
+    fn ratio(numer: i64, denom: i64) -> BigRational {
+        BigRational::new(numer.into(), denom.into())
+    }
+
+    #[test]
+    fn test_lagrange() {
+        let lagrange = Lagrange::from_sequence(&[10, 13, 16, 21, 30, 45]).unwrap();
+        assert_eq!(lagrange.degree(), Some(3));
+        assert_eq!(
+            lagrange.coefficients(),
+            [ratio(10, 1), ratio(11, 3), ratio(-1, 1), ratio(1, 3)]
+        );
+        assert_eq!(lagrange.value_at(&ratio(6, 1)), ratio(68, 1));
+        assert_eq!(lagrange.value_at(&ratio(-1, 1)), ratio(5, 1));
+
+        // Squares sampled at odd positions, evaluated between them.
+        let points = [(1, 1), (3, 9), (5, 25)].map(|(x, y)| (ratio(x, 1), ratio(y, 1)));
+        let lagrange = Lagrange::fit(&points).unwrap();
+        assert_eq!(
+            lagrange.coefficients(),
+            [ratio(0, 1), ratio(0, 1), ratio(1, 1)]
+        );
+        assert_eq!(lagrange.value_at(&ratio(1, 2)), ratio(1, 4));
+
+        // The same squares at half positions, and a line through fractions.
+        let points = [(1, 1), (3, 9), (5, 25)].map(|(x, y)| (ratio(x, 2), ratio(y, 4)));
+        let lagrange = Lagrange::fit(&points).unwrap();
+        assert_eq!(
+            lagrange.coefficients(),
+            [ratio(0, 1), ratio(0, 1), ratio(1, 1)]
+        );
+        let points = [(ratio(0, 1), ratio(1, 3)), (ratio(2, 1), ratio(1, 1))];
+        let lagrange = Lagrange::fit(&points).unwrap();
+        assert_eq!(lagrange.coefficients(), [ratio(1, 3), ratio(1, 3)]);
+        assert_eq!(lagrange.value_at(&ratio(100, 1)), ratio(101, 3));
+
+        assert_eq!(Lagrange::from_sequence(&[0, 0]).unwrap().degree(), None);
+        assert!(Lagrange::from_sequence(&[]).is_err());
+        assert!(Lagrange::fit(&[(ratio(1, 1), ratio(1, 1)), (ratio(1, 1), ratio(2, 1))]).is_err());
+    }
+
+    #[test]
+    fn test_lagrange_matches_differences() {
+        let input = include_str!("../input");
+        for sequence in parse_sequences(input).unwrap() {
+            let lagrange = Lagrange::from_sequence(&sequence).unwrap();
+            let next = ratio(sequence.len() as i64, 1);
+
+            assert_eq!(
+                lagrange.value_at(&next),
+                ratio(get_next_number(&sequence).unwrap() as i64, 1)
+            );
+            assert_eq!(
+                lagrange.value_at(&ratio(-1, 1)),
+                ratio(get_previous_number(&sequence).unwrap() as i64, 1)
+            );
+            assert_eq!(lagrange.degree(), Newton::fit(&sequence).unwrap().degree());
+        }
+
+        let example = include_str!("../example");
+        let total = interpolate(example, &ratio(6, 1))
+            .unwrap()
+            .into_iter()
+            .map(|(_, value)| value)
+            .fold(BigRational::zero(), |sum, value| sum + value);
+        assert_eq!(total, ratio(114, 1));
     }
 }
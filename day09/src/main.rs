@@ -1,5 +1,25 @@
+use std::env;
+
+use itertools::Itertools;
+use num_rational::BigRational;
+
 fn main() {
     let input = include_str!("../input");
-    println!("part1: {}", day09::part1(input).unwrap());
-    println!("part2: {}", day09::part2(input).unwrap());
+
+    match env::args().nth(1).as_deref() {
+        Some("interpolate") => {
+            let position = env::args()
+                .nth(2)
+                .expect("interpolate needs a position")
+                .parse::<BigRational>()
+                .unwrap();
+            for (coefficients, value) in day09::interpolate(input, &position).unwrap() {
+                println!("{} => {}", coefficients.iter().join(" "), value);
+            }
+        }
+        _ => {
+            println!("part1: {}", day09::part1(input).unwrap());
+            println!("part2: {}", day09::part2(input).unwrap());
+        }
+    }
 }
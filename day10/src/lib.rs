@@ -1,33 +1,89 @@
-use anyhow::{Context, Ok, Result};
+use std::collections::HashSet;
+
+use anyhow::{bail, Context, Ok, Result};
 use itertools::Itertools;
 
 pub fn part1(input: &str) -> Result<usize> {
-    let ground: Ground = input.try_into()?;
+    let pipe_loop: PipeLoop = input.try_into()?;
 
-    let binding = ground.first_step_choice();
-    let (first_position, second_positon) = binding
-        .iter()
-        .collect_tuple()
-        .context("No initial step found")?;
-    let mut first_position = *first_position;
-    let mut second_positon = *second_positon;
-
-    let mut steps = 1;
-
-    while first_position.x != second_positon.x || first_position.y != second_positon.y {
-        steps += 1;
-        first_position = ground
-            .next_step(&first_position)
-            .context("No next step found")?;
-        second_positon = ground
-            .next_step(&second_positon)
-            .context("No next step found")?;
+    Ok(pipe_loop.farthest().1)
+}
+
+pub fn part2(input: &str) -> Result<usize> {
+    let pipe_loop: PipeLoop = input.try_into()?;
+
+    Ok(pipe_loop.enclosed())
+}
+
+/// The main loop through `S`, with the pipe hidden under `S` worked out
+/// from the neighbours that connect to it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PipeLoop {
+    start_pipe: Pipe,
+    positions: Vec<(usize, usize)>,
+}
+
+impl PipeLoop {
+    pub fn start_pipe(&self) -> Pipe {
+        self.start_pipe
     }
 
-    Ok(steps)
+    /// Loop tiles as `(x, y)` in walking order, starting at `S`.
+    pub fn positions(&self) -> &[(usize, usize)] {
+        &self.positions
+    }
+
+    /// The tile farthest along the loop from `S`, and its distance.
+    pub fn farthest(&self) -> ((usize, usize), usize) {
+        let distance = self.positions.len() / 2;
+        (self.positions[distance], distance)
+    }
+
+    /// Tiles strictly inside the loop.
+    pub fn enclosed(&self) -> usize {
+        // Shoelace gives the area enclosed by the tile centres, and Pick's
+        // theorem turns it into the tiles strictly inside the loop.
+        let double_area = self
+            .positions
+            .iter()
+            .circular_tuple_windows()
+            .map(|(a, b)| (a.0 * b.1) as isize - (b.0 * a.1) as isize)
+            .sum::<isize>()
+            .unsigned_abs();
+
+        (double_area + 2 - self.positions.len()) / 2
+    }
 }
 
-#[derive(Debug, Clone, Copy)]
+impl TryFrom<&str> for PipeLoop {
+    type Error = anyhow::Error;
+
+    fn try_from(value: &str) -> Result<Self> {
+        let ground: Ground = value.try_into()?;
+        Self::from_ground(&ground)
+    }
+}
+
+impl PipeLoop {
+    fn from_ground(ground: &Ground) -> Result<Self> {
+        let sides = ground.start_sides();
+        let start_pipe = match sides[..] {
+            [] => bail!("Start connects to no pipe"),
+            [_] => bail!("Start connects to only one pipe"),
+            [first, second] => {
+                Pipe::from_sides([first, second]).context("Start pipe can't be inferred")?
+            }
+            _ => bail!("Start connects to {} pipes", sides.len()),
+        };
+
+        Ok(Self {
+            start_pipe,
+            positions: ground.loop_positions()?,
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 enum Direction {
     Up,
     Down,
@@ -35,6 +91,80 @@ enum Direction {
     Right,
 }
 
+impl Direction {
+    const ALL: [Direction; 4] = [
+        Direction::Up,
+        Direction::Down,
+        Direction::Left,
+        Direction::Right,
+    ];
+
+    fn opposite(self) -> Direction {
+        match self {
+            Direction::Up => Direction::Down,
+            Direction::Down => Direction::Up,
+            Direction::Left => Direction::Right,
+            Direction::Right => Direction::Left,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Pipe {
+    Vertical,
+    Horizontal,
+    UpRight,
+    UpLeft,
+    DownLeft,
+    DownRight,
+}
+
+impl Pipe {
+    /// Each pipe with its symbol and the two sides it opens to.
+    const TABLE: [(Pipe, char, [Direction; 2]); 6] = [
+        (Pipe::Vertical, '|', [Direction::Up, Direction::Down]),
+        (Pipe::Horizontal, '-', [Direction::Left, Direction::Right]),
+        (Pipe::UpRight, 'L', [Direction::Up, Direction::Right]),
+        (Pipe::UpLeft, 'J', [Direction::Up, Direction::Left]),
+        (Pipe::DownLeft, '7', [Direction::Down, Direction::Left]),
+        (Pipe::DownRight, 'F', [Direction::Down, Direction::Right]),
+    ];
+
+    pub fn from_char(c: char) -> Option<Pipe> {
+        Self::TABLE
+            .iter()
+            .find(|(_, symbol, _)| *symbol == c)
+            .map(|(pipe, _, _)| *pipe)
+    }
+
+    pub fn to_char(self) -> char {
+        self.entry().1
+    }
+
+    fn from_sides(sides: [Direction; 2]) -> Option<Pipe> {
+        Self::TABLE
+            .iter()
+            .find(|(_, _, open)| open.contains(&sides[0]) && open.contains(&sides[1]))
+            .map(|(pipe, _, _)| *pipe)
+    }
+
+    fn sides(self) -> [Direction; 2] {
+        self.entry().2
+    }
+
+    fn entry(self) -> (Pipe, char, [Direction; 2]) {
+        Self::TABLE[self as usize]
+    }
+
+    /// Direction to leave by after entering while moving `direction`.
+    fn exit(self, direction: Direction) -> Option<Direction> {
+        match self.sides() {
+            [side, other] | [other, side] if side == direction.opposite() => Some(other),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Debug, Clone, Copy)]
 struct Position {
     direction: Direction,
@@ -48,174 +178,78 @@ struct Ground {
 }
 
 impl Ground {
-    fn first_step_choice(&self) -> Vec<Position> {
-        let mut positions = Vec::new();
-        let (x, y) = self.start;
+    /// Tiles of the loop in walking order, starting at `S`.
+    fn loop_positions(&self) -> Result<Vec<(usize, usize)>> {
+        let mut position = *self
+            .first_step_choice()
+            .first()
+            .context("No initial step found")?;
+        let mut positions = vec![self.start];
 
-        if y > 0 {
-            match self.map[y - 1][x] {
-                '|' => positions.push(Position {
-                    direction: Direction::Up,
-                    x,
-                    y: y - 1,
-                }),
-                'F' => positions.push(Position {
-                    direction: Direction::Right,
-                    x,
-                    y: y - 1,
-                }),
-                '7' => positions.push(Position {
-                    direction: Direction::Left,
-                    x,
-                    y: y - 1,
-                }),
-                _ => (),
-            }
+        while (position.x, position.y) != self.start {
+            positions.push((position.x, position.y));
+            position = match self.next_step(&position) {
+                Some(next) => next,
+                None if self.neighbour(position.x, position.y, position.direction)
+                    == Some(self.start) =>
+                {
+                    Position {
+                        x: self.start.0,
+                        y: self.start.1,
+                        ..position
+                    }
+                }
+                None => bail!("Loop is not closed"),
+            };
         }
 
-        if y < self.map.len() - 1 {
-            match self.map[y + 1][x] {
-                '|' => positions.push(Position {
-                    direction: Direction::Down,
-                    x,
-                    y: y + 1,
-                }),
-                'J' => positions.push(Position {
-                    direction: Direction::Left,
-                    x,
-                    y: y + 1,
-                }),
-                'L' => positions.push(Position {
-                    direction: Direction::Right,
-                    x,
-                    y: y + 1,
-                }),
-                _ => (),
-            }
-        }
+        Ok(positions)
+    }
 
-        if x > 0 {
-            match self.map[y][x - 1] {
-                '-' => positions.push(Position {
-                    direction: Direction::Left,
-                    x: x - 1,
-                    y,
-                }),
-                'L' => positions.push(Position {
-                    direction: Direction::Up,
-                    x: x - 1,
-                    y,
-                }),
-                'F' => positions.push(Position {
-                    direction: Direction::Down,
-                    x: x - 1,
-                    y,
-                }),
-                _ => (),
-            }
-        }
+    /// The tile next to `(x, y)` towards `direction`, if it is on the map.
+    fn neighbour(&self, x: usize, y: usize, direction: Direction) -> Option<(usize, usize)> {
+        let (x, y) = match direction {
+            Direction::Up => (x, y.checked_sub(1)?),
+            Direction::Down => (x, y + 1),
+            Direction::Left => (x.checked_sub(1)?, y),
+            Direction::Right => (x + 1, y),
+        };
+        self.map.get(y)?.get(x)?;
 
-        if x < self.map[0].len() - 1 {
-            match self.map[y][x + 1] {
-                '-' => positions.push(Position {
-                    direction: Direction::Right,
-                    x: x + 1,
-                    y,
-                }),
-                'J' => positions.push(Position {
-                    direction: Direction::Up,
-                    x: x + 1,
-                    y,
-                }),
-                '7' => positions.push(Position {
-                    direction: Direction::Down,
-                    x: x + 1,
-                    y,
-                }),
-                _ => (),
-            }
-        }
+        Some((x, y))
+    }
+
+    /// Moves from `(x, y)` towards `direction` onto a pipe that accepts it.
+    fn step(&self, x: usize, y: usize, direction: Direction) -> Option<Position> {
+        let (x, y) = self.neighbour(x, y, direction)?;
+        let pipe = Pipe::from_char(self.map[y][x])?;
 
-        positions
+        Some(Position {
+            direction: pipe.exit(direction)?,
+            x,
+            y,
+        })
+    }
+
+    /// Sides of `S` whose neighbour is a pipe leading back into it.
+    fn start_sides(&self) -> Vec<Direction> {
+        let (x, y) = self.start;
+        Direction::ALL
+            .into_iter()
+            .filter(|direction| self.step(x, y, *direction).is_some())
+            .collect()
+    }
+
+    fn first_step_choice(&self) -> Vec<Position> {
+        let (x, y) = self.start;
+        Direction::ALL
+            .into_iter()
+            .filter_map(|direction| self.step(x, y, direction))
+            .collect()
     }
 
     fn next_step(&self, position: &Position) -> Option<Position> {
-        let (x, y) = (position.x, position.y);
-        match position.direction {
-            Direction::Up if y > 0 => match self.map[y - 1][x] {
-                '|' => Some(Position {
-                    direction: Direction::Up,
-                    x,
-                    y: y - 1,
-                }),
-                'F' => Some(Position {
-                    direction: Direction::Right,
-                    x,
-                    y: y - 1,
-                }),
-                '7' => Some(Position {
-                    direction: Direction::Left,
-                    x,
-                    y: y - 1,
-                }),
-                _ => None,
-            },
-            Direction::Down if y < self.map.len() => match self.map[y + 1][x] {
-                '|' => Some(Position {
-                    direction: Direction::Down,
-                    x,
-                    y: y + 1,
-                }),
-                'J' => Some(Position {
-                    direction: Direction::Left,
-                    x,
-                    y: y + 1,
-                }),
-                'L' => Some(Position {
-                    direction: Direction::Right,
-                    x,
-                    y: y + 1,
-                }),
-                _ => None,
-            },
-            Direction::Left if x > 0 => match self.map[y][x - 1] {
-                '-' => Some(Position {
-                    direction: Direction::Left,
-                    x: x - 1,
-                    y,
-                }),
-                'L' => Some(Position {
-                    direction: Direction::Up,
-                    x: x - 1,
-                    y,
-                }),
-                'F' => Some(Position {
-                    direction: Direction::Down,
-                    x: x - 1,
-                    y,
-                }),
-                _ => None,
-            },
-            Direction::Right if x < self.map[0].len() => match self.map[y][x + 1] {
-                '-' => Some(Position {
-                    direction: Direction::Right,
-                    x: x + 1,
-                    y,
-                }),
-                'J' => Some(Position {
-                    direction: Direction::Up,
-                    x: x + 1,
-                    y,
-                }),
-                '7' => Some(Position {
-                    direction: Direction::Down,
-                    x: x + 1,
-                    y,
-                }),
-                _ => None,
-            },
-            _ => None,
-        }
+        self.step(position.x, position.y, position.direction)
     }
 }
 
@@ -247,6 +281,194 @@ impl TryFrom<&str> for Ground {
     }
 }
 
+/// A map tile as seen from the main loop.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Loop(Pipe),
+    /// Anything off the loop: a stray pipe or bare ground.
+    Junk {
+        pipe: Option<Pipe>,
+        enclosed: bool,
+    },
+}
+
+/// Every tile of `Ground.map` classified against the main loop, with `S`
+/// shown as the pipe it stands for.
+pub fn classify(input: &str) -> Result<Vec<Vec<Tile>>> {
+    let ground: Ground = input.try_into()?;
+    let pipe_loop = PipeLoop::from_ground(&ground)?;
+    let on_loop = pipe_loop.positions.iter().copied().collect::<HashSet<_>>();
+
+    let tiles = ground
+        .map
+        .iter()
+        .enumerate()
+        .map(|(y, row)| {
+            // Scanline parity: crossing a loop pipe that opens upwards
+            // flips between outside and inside.
+            let mut inside = false;
+            row.iter()
+                .enumerate()
+                .map(|(x, c)| {
+                    let pipe = if (x, y) == ground.start {
+                        Some(pipe_loop.start_pipe)
+                    } else {
+                        Pipe::from_char(*c)
+                    };
+                    match pipe {
+                        Some(pipe) if on_loop.contains(&(x, y)) => {
+                            if pipe.sides().contains(&Direction::Up) {
+                                inside = !inside;
+                            }
+                            Tile::Loop(pipe)
+                        }
+                        pipe => Tile::Junk {
+                            pipe,
+                            enclosed: inside,
+                        },
+                    }
+                })
+                .collect_vec()
+        })
+        .collect_vec();
+
+    Ok(tiles)
+}
+
+impl Pipe {
+    fn light_glyph(self) -> char {
+        match self {
+            Pipe::Vertical => '│',
+            Pipe::Horizontal => '─',
+            Pipe::UpRight => '└',
+            Pipe::UpLeft => '┘',
+            Pipe::DownLeft => '┐',
+            Pipe::DownRight => '┌',
+        }
+    }
+
+    fn heavy_glyph(self) -> char {
+        match self {
+            Pipe::Vertical => '┃',
+            Pipe::Horizontal => '━',
+            Pipe::UpRight => '┗',
+            Pipe::UpLeft => '┛',
+            Pipe::DownLeft => '┓',
+            Pipe::DownRight => '┏',
+        }
+    }
+}
+
+/// The map in box-drawing characters for a terminal: the loop bold, junk
+/// pipes dimmed and enclosed tiles in green.
+pub fn render_ansi(input: &str) -> Result<String> {
+    const LOOP: &str = "\x1b[1;33m";
+    const JUNK: &str = "\x1b[2m";
+    const ENCLOSED: &str = "\x1b[32m";
+    const RESET: &str = "\x1b[0m";
+
+    let lines = classify(input)?
+        .iter()
+        .map(|row| {
+            row.iter()
+                .map(|tile| match tile {
+                    Tile::Loop(pipe) => format!("{LOOP}{}{RESET}", pipe.heavy_glyph()),
+                    Tile::Junk {
+                        pipe: Some(pipe),
+                        enclosed: false,
+                    } => format!("{JUNK}{}{RESET}", pipe.light_glyph()),
+                    Tile::Junk {
+                        pipe: Some(pipe),
+                        enclosed: true,
+                    } => format!("{ENCLOSED}{}{RESET}", pipe.light_glyph()),
+                    Tile::Junk {
+                        pipe: None,
+                        enclosed: true,
+                    } => format!("{ENCLOSED}•{RESET}"),
+                    Tile::Junk {
+                        pipe: None,
+                        enclosed: false,
+                    } => " ".to_string(),
+                })
+                .join("")
+        })
+        .join("\n");
+
+    Ok(lines)
+}
+
+/// The map as an SVG drawing, with pipes as strokes from each tile centre
+/// to the sides it opens to.
+pub fn render_svg(input: &str) -> Result<String> {
+    const CELL: usize = 10;
+
+    let tiles = classify(input)?;
+    let height = tiles.len() * CELL;
+    let width = tiles.iter().map(Vec::len).max().unwrap_or(0) * CELL;
+
+    let strokes = |x: usize, y: usize, pipe: Pipe| {
+        let (cx, cy) = (x * CELL + CELL / 2, y * CELL + CELL / 2);
+        pipe.sides()
+            .iter()
+            .map(|side| {
+                let (ex, ey) = match side {
+                    Direction::Up => (cx, cy - CELL / 2),
+                    Direction::Down => (cx, cy + CELL / 2),
+                    Direction::Left => (cx - CELL / 2, cy),
+                    Direction::Right => (cx + CELL / 2, cy),
+                };
+                format!("M{cx} {cy}L{ex} {ey}")
+            })
+            .join("")
+    };
+
+    let mut enclosed = vec![];
+    let mut junk = vec![];
+    let mut main_loop = vec![];
+    for (y, row) in tiles.iter().enumerate() {
+        for (x, tile) in row.iter().enumerate() {
+            match tile {
+                Tile::Loop(pipe) => main_loop.push(strokes(x, y, *pipe)),
+                Tile::Junk {
+                    pipe,
+                    enclosed: is_enclosed,
+                } => {
+                    if *is_enclosed {
+                        enclosed.push(format!(
+                            r#"<rect x="{}" y="{}" width="{CELL}" height="{CELL}"/>"#,
+                            x * CELL,
+                            y * CELL
+                        ));
+                    }
+                    if let Some(pipe) = pipe {
+                        junk.push(strokes(x, y, *pipe));
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(format!(
+        concat!(
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{width}" height="{height}" viewBox="0 0 {width} {height}">"#,
+            "\n",
+            r#"<rect width="{width}" height="{height}" fill="white"/>"#,
+            "\n",
+            r##"<g fill="#9c9">{enclosed}</g>"##,
+            "\n",
+            r##"<path d="{junk}" stroke="#bbb" stroke-width="1" fill="none"/>"##,
+            "\n",
+            r##"<path d="{main_loop}" stroke="#c33" stroke-width="3" stroke-linecap="round" fill="none"/>"##,
+            "\n</svg>\n"
+        ),
+        width = width,
+        height = height,
+        enclosed = enclosed.join(""),
+        junk = junk.join(""),
+        main_loop = main_loop.join(""),
+    ))
+}
+
 #[cfg(test)]
 mod tests {
     #[test]
@@ -257,4 +479,174 @@ mod tests {
         let input = include_str!("../example2");
         assert_eq!(super::part1(input).unwrap(), 8);
     }
+
+    #[test]
+    fn pipe_loop() {
+        let input = include_str!("../example1");
+        let pipe_loop: super::PipeLoop = input.try_into().unwrap();
+        assert_eq!(pipe_loop.start_pipe(), super::Pipe::DownRight);
+        assert_eq!(
+            pipe_loop.positions(),
+            [
+                (1, 1),
+                (1, 2),
+                (1, 3),
+                (2, 3),
+                (3, 3),
+                (3, 2),
+                (3, 1),
+                (2, 1)
+            ]
+        );
+        assert_eq!(pipe_loop.farthest(), ((3, 3), 4));
+
+        let input = include_str!("../example2");
+        let pipe_loop: super::PipeLoop = input.try_into().unwrap();
+        assert_eq!(pipe_loop.start_pipe(), super::Pipe::DownRight);
+        assert_eq!(pipe_loop.positions().len(), 16);
+        assert_eq!(pipe_loop.farthest(), ((4, 2), 8));
+
+        let input = include_str!("../example5");
+        let pipe_loop: super::PipeLoop = input.try_into().unwrap();
+        assert_eq!(pipe_loop.start_pipe(), super::Pipe::DownRight);
+    }
+
+    #[test]
+    fn start_connections() {
+        let error = |input: &str| super::PipeLoop::try_from(input).unwrap_err().to_string();
+
+        assert_eq!(error("...\n.S.\n..."), "Start connects to no pipe");
+        assert_eq!(error("...\n.S-\n..."), "Start connects to only one pipe");
+        assert_eq!(error(".|.\n-S-\n.|."), "Start connects to 4 pipes");
+        assert_eq!(error(".|.\n-S.\n..."), "Loop is not closed");
+    }
+
+    #[test]
+    fn pipe_table() {
+        use super::{Direction, Pipe};
+
+        for c in "|-LJ7F".chars() {
+            let pipe = Pipe::from_char(c).unwrap();
+            assert_eq!(pipe.to_char(), c);
+            assert_eq!(Pipe::from_sides(pipe.sides()), Some(pipe));
+        }
+        assert_eq!(Pipe::from_char('.'), None);
+        assert_eq!(Pipe::from_char('S'), None);
+
+        assert_eq!(Pipe::UpRight.exit(Direction::Down), Some(Direction::Right));
+        assert_eq!(Pipe::UpRight.exit(Direction::Left), Some(Direction::Up));
+        assert_eq!(Pipe::UpRight.exit(Direction::Up), None);
+        assert_eq!(Pipe::Vertical.exit(Direction::Right), None);
+    }
+
+    #[test]
+    fn loops_on_map_edges() {
+        // The loop runs along every edge, with S in each corner in turn.
+        for input in [
+            "S-7\n|.|\nL-J",
+            "F-S\n|.|\nL-J",
+            "F-7\n|.|\nS-J",
+            "F-7\n|.|\nL-S",
+        ] {
+            assert_eq!(super::part1(input).unwrap(), 4);
+            assert_eq!(super::part2(input).unwrap(), 1);
+        }
+
+        let input = "F--S\n|..|\n|..|\nL--J";
+        assert_eq!(super::part1(input).unwrap(), 6);
+        assert_eq!(super::part2(input).unwrap(), 4);
+
+        // Pipes pointing off the bottom and left edges end the walk instead
+        // of reading past the map.
+        let error = |input: &str| super::PipeLoop::try_from(input).unwrap_err().to_string();
+        assert_eq!(error("F-S\n|.|\nL-|"), "Loop is not closed");
+        assert_eq!(error("F-S\n|.|\n--J"), "Loop is not closed");
+    }
+
+    #[test]
+    fn classify() {
+        use super::{Pipe, Tile};
+
+        for example in [
+            include_str!("../example3"),
+            include_str!("../example4"),
+            include_str!("../example5"),
+            include_str!("../example6"),
+        ] {
+            let enclosed = super::classify(example)
+                .unwrap()
+                .iter()
+                .flatten()
+                .filter(|tile| matches!(tile, Tile::Junk { enclosed: true, .. }))
+                .count();
+            assert_eq!(enclosed, super::part2(example).unwrap());
+        }
+
+        let tiles = super::classify(include_str!("../example1")).unwrap();
+        assert_eq!(tiles[1][1], Tile::Loop(Pipe::DownRight));
+        assert_eq!(
+            tiles[0][0],
+            Tile::Junk {
+                pipe: Some(Pipe::Horizontal),
+                enclosed: false
+            }
+        );
+        assert_eq!(
+            tiles[2][2],
+            Tile::Junk {
+                pipe: Some(Pipe::DownLeft),
+                enclosed: true
+            }
+        );
+    }
+
+    #[test]
+    fn render() {
+        let input = include_str!("../example1");
+        let ansi = super::render_ansi(input).unwrap();
+        assert_eq!(ansi.lines().count(), 5);
+        assert!(ansi.contains("\x1b[1;33m┏\x1b[0m"));
+        assert!(ansi.contains("\x1b[32m┐\x1b[0m"));
+        assert!(ansi.contains("\x1b[2m┘\x1b[0m"));
+
+        let plain = |line: &str| {
+            line.replace("\x1b[1;33m", "")
+                .replace("\x1b[2m", "")
+                .replace("\x1b[32m", "")
+                .replace("\x1b[0m", "")
+        };
+        assert_eq!(
+            ansi.lines().map(plain).collect::<Vec<_>>(),
+            ["─└│┌┐", "┐┏━┓│", "└┃┐┃│", "─┗━┛│", "└│─┘┌"]
+        );
+
+        let svg = super::render_svg(input).unwrap();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.contains(r#"width="50" height="50""#));
+        assert!(svg.contains(r#"<rect x="20" y="20" width="10" height="10"/>"#));
+        assert!(svg.trim_end().ends_with("</svg>"));
+    }
+
+    #[test]
+    fn part2() {
+        let input = include_str!("../example1");
+        assert_eq!(super::part2(input).unwrap(), 1);
+
+        let input = include_str!("../example3");
+        assert_eq!(super::part2(input).unwrap(), 4);
+
+        // Tiles outside the loop that are only reachable by squeezing
+        // between pipes.
+        let input = include_str!("../example4");
+        assert_eq!(super::part2(input).unwrap(), 4);
+
+        let input = include_str!("../example5");
+        assert_eq!(super::part2(input).unwrap(), 8);
+
+        let input = include_str!("../example6");
+        assert_eq!(super::part2(input).unwrap(), 10);
+
+        // The smallest loop encloses nothing.
+        assert_eq!(super::part2("S7\nLJ").unwrap(), 0);
+    }
 }
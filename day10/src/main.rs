@@ -1,4 +1,14 @@
+use std::env;
+
 fn main() {
     let input = include_str!("../input");
-    println!("Part 1: {}", day10::part1(input).unwrap());
+
+    match env::args().nth(1).as_deref() {
+        Some("render") => println!("{}", day10::render_ansi(input).unwrap()),
+        Some("svg") => print!("{}", day10::render_svg(input).unwrap()),
+        _ => {
+            println!("Part 1: {}", day10::part1(input).unwrap());
+            println!("Part 2: {}", day10::part2(input).unwrap());
+        }
+    }
 }
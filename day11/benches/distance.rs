@@ -0,0 +1,20 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use day11::{parse_galaxies, sum_galaxies_distance, sum_galaxy_distances, Image};
+
+fn distance(c: &mut Criterion) {
+    let input = include_str!("../input");
+    let image = Image::parse_with_distance_factor(input, 1000000).unwrap();
+    let galaxies = parse_galaxies(input).unwrap();
+
+    let mut group = c.benchmark_group("distance");
+    group.bench_function("combinations", |b| {
+        b.iter(|| sum_galaxies_distance(black_box(&image)))
+    });
+    group.bench_function("sorted_prefix_sums", |b| {
+        b.iter(|| sum_galaxy_distances(black_box(&galaxies), 1000000))
+    });
+    group.finish();
+}
+
+criterion_group!(benches, distance);
+criterion_main!(benches);
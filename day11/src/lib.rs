@@ -1,22 +1,72 @@
 use std::collections::HashSet;
 
-use anyhow::{Context, Ok, Result};
+use anyhow::{bail, Context, Ok, Result};
 use itertools::Itertools;
 pub fn part1(input: &str) -> Result<usize> {
-    let image = Image::parse_with_distance_factor(input, 2)?;
-    let sum = sum_galaxies_distance(image);
+    let galaxies = parse_galaxies(input)?;
+    let sum = sum_galaxy_distances(&galaxies, 2);
 
     Ok(sum)
 }
 
 pub fn part2(input: &str) -> Result<usize> {
-    let image = Image::parse_with_distance_factor(input, 1000000)?;
-    let sum = sum_galaxies_distance(image);
+    let galaxies = parse_galaxies(input)?;
+    let sum = sum_galaxy_distances(&galaxies, 1000000);
 
     Ok(sum)
 }
 
-fn sum_galaxies_distance(image: Image) -> usize {
+/// Galaxy positions as `(x, y)` before any expansion.
+pub fn parse_galaxies(input: &str) -> Result<Vec<(usize, usize)>> {
+    if input.lines().next().is_none() {
+        bail!("empty image");
+    }
+
+    Ok(input
+        .lines()
+        .enumerate()
+        .flat_map(|(y, line)| {
+            line.char_indices()
+                .filter(|(_, c)| *c == '#')
+                .map(move |(x, _)| (x, y))
+        })
+        .collect())
+}
+
+/// Sum of the expanded Manhattan distances between every pair of galaxies,
+/// taken one axis at a time in O(n log n).
+pub fn sum_galaxy_distances(galaxies: &[(usize, usize)], distance_factor: usize) -> usize {
+    axis_distance(galaxies.iter().map(|(x, _)| *x), distance_factor)
+        + axis_distance(galaxies.iter().map(|(_, y)| *y), distance_factor)
+}
+
+fn axis_distance(coordinates: impl Iterator<Item = usize>, distance_factor: usize) -> usize {
+    let coordinates = coordinates.sorted_unstable().collect_vec();
+
+    // A line is empty exactly when no galaxy sits on it, so the gaps
+    // between sorted coordinates give the expansion. Each galaxy then adds
+    // its distance to all the ones before it through the prefix sum.
+    let mut expanded = 0;
+    let mut prefix = 0;
+    coordinates
+        .iter()
+        .enumerate()
+        .map(|(i, coordinate)| {
+            if i > 0 {
+                let gap = coordinate - coordinates[i - 1];
+                if gap > 0 {
+                    expanded += (gap - 1) * distance_factor + 1;
+                }
+            }
+            let sum = expanded * i - prefix;
+            prefix += expanded;
+            sum
+        })
+        .sum()
+}
+
+/// Reference sum over every pair of galaxies in an expanded `Image`.
+pub fn sum_galaxies_distance(image: &Image) -> usize {
     image
         .galaxies
         .iter()
@@ -26,12 +76,12 @@ fn sum_galaxies_distance(image: Image) -> usize {
         .sum()
 }
 
-struct Image {
+pub struct Image {
     galaxies: Vec<(usize, usize)>,
 }
 
 impl Image {
-    fn parse_with_distance_factor(input: &str, distance_factor: usize) -> Result<Self> {
+    pub fn parse_with_distance_factor(input: &str, distance_factor: usize) -> Result<Self> {
         let distance_factor = distance_factor - 1;
 
         let image = input
@@ -111,9 +161,31 @@ mod tests {
         let input = include_str!("../example");
         let image = Image::parse_with_distance_factor(input, 10).unwrap();
 
-        assert_eq!(sum_galaxies_distance(image), 1030);
+        assert_eq!(sum_galaxies_distance(&image), 1030);
 
         let image = Image::parse_with_distance_factor(input, 100).unwrap();
-        assert_eq!(sum_galaxies_distance(image), 8410);
+        assert_eq!(sum_galaxies_distance(&image), 8410);
+    }
+
+    #[test]
+    fn test_sum_galaxy_distances() {
+        let input = include_str!("../example");
+        let galaxies = parse_galaxies(input).unwrap();
+
+        assert_eq!(sum_galaxy_distances(&galaxies, 2), 374);
+        assert_eq!(sum_galaxy_distances(&galaxies, 10), 1030);
+        assert_eq!(sum_galaxy_distances(&galaxies, 100), 8410);
+        assert_eq!(sum_galaxy_distances(&galaxies[..1], 100), 0);
+        assert_eq!(sum_galaxy_distances(&[], 100), 0);
+
+        let input = include_str!("../input");
+        let galaxies = parse_galaxies(input).unwrap();
+        for factor in [1, 2, 1000000] {
+            let image = Image::parse_with_distance_factor(input, factor).unwrap();
+            assert_eq!(
+                sum_galaxy_distances(&galaxies, factor),
+                sum_galaxies_distance(&image)
+            );
+        }
     }
 }